@@ -0,0 +1,156 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DataEnum, DeriveInput, Error, Fields, Ident};
+
+/// Implement the trait for an enum of which all variants are without fields.
+///
+/// The value is stored as the index of the variant.
+fn derive_enum_impl(name: Ident, data: DataEnum) -> Result<TokenStream, TokenStream> {
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|variant| variant.fields != Fields::Unit)
+    {
+        return Err(TokenStream::from(
+            Error::new_spanned(variant, "only enum variants without fields are supported")
+                .to_compile_error(),
+        ));
+    }
+
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let indices = (0..variants.len() as u64).collect::<Vec<_>>();
+
+    let result = quote! {
+        impl const_tweaker::Tweakable for #name {
+            fn type_name() -> &'static str {
+                stringify!(#name)
+            }
+
+            fn to_json(&self) -> const_tweaker::serde_json::Value {
+                let index: u64 = match self {
+                    #( #name::#variants => #indices, )*
+                };
+
+                const_tweaker::serde_json::Value::from(index)
+            }
+
+            fn from_json(
+                json: const_tweaker::serde_json::Value,
+            ) -> Result<Self, const_tweaker::TweakerError> {
+                match json.as_u64() {
+                    #( Some(#indices) => Ok(#name::#variants), )*
+                    _ => Err(const_tweaker::TweakerError::InvalidJson(format!(
+                        "expected a variant index of {}, got {}",
+                        stringify!(#name),
+                        json
+                    ))),
+                }
+            }
+
+            fn render_html(key: &str, value: &Self) -> String {
+                const_tweaker::enum_widget(
+                    key,
+                    &[ #( stringify!(#variants), )* ],
+                    Self::to_json(value).as_u64().unwrap_or_default(),
+                )
+            }
+        }
+    };
+
+    Ok(result.into())
+}
+
+/// Proc macro call but with a result, which allows the use of `?`.
+fn derive_tweakable_impl(input: DeriveInput) -> Result<TokenStream, TokenStream> {
+    let name = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match input.data {
+        Data::Struct(data) => match data.fields {
+            Fields::Named(fields) => fields.named,
+            fields => {
+                return Err(TokenStream::from(
+                    Error::new_spanned(fields, "only structs with named fields are supported")
+                        .to_compile_error(),
+                ))
+            }
+        },
+        Data::Enum(data) => return derive_enum_impl(name, data),
+        Data::Union(_) => {
+            return Err(TokenStream::from(
+                Error::new_spanned(name, "unions are not supported").to_compile_error(),
+            ))
+        }
+    };
+
+    let idents = fields
+        .iter()
+        .map(|field| {
+            field
+                .ident
+                .clone()
+                .expect("Named fields have an identifier")
+        })
+        .collect::<Vec<_>>();
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+
+    let result = quote! {
+        impl #impl_generics const_tweaker::Tweakable for #name #ty_generics #where_clause {
+            fn type_name() -> &'static str {
+                stringify!(#name)
+            }
+
+            fn to_json(&self) -> const_tweaker::serde_json::Value {
+                let mut json = const_tweaker::serde_json::Map::new();
+                #(
+                    json.insert(
+                        stringify!(#idents).to_string(),
+                        const_tweaker::TweakableField::to_json(&self.#idents),
+                    );
+                )*
+
+                const_tweaker::serde_json::Value::Object(json)
+            }
+
+            fn from_json(
+                json: const_tweaker::serde_json::Value,
+            ) -> Result<Self, const_tweaker::TweakerError> {
+                Ok(Self {
+                    #(
+                        #idents: const_tweaker::struct_field(&json, stringify!(#idents))?,
+                    )*
+                })
+            }
+
+            fn render_html(key: &str, value: &Self) -> String {
+                const_tweaker::struct_widget(key, &[
+                    #(
+                        (
+                            stringify!(#idents),
+                            <#types as const_tweaker::TweakableField>::input_type(),
+                            const_tweaker::TweakableField::to_json(&value.#idents),
+                        ),
+                    )*
+                ])
+            }
+        }
+    };
+
+    Ok(result.into())
+}
+
+/// Implement the `Tweakable` trait for a struct of which all fields are supported primitive types
+/// or an enum of which all variants are without fields.
+#[proc_macro_derive(Tweakable)]
+pub fn derive_tweakable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match derive_tweakable_impl(input) {
+        Ok(result) => result,
+        Err(err) => err,
+    }
+}
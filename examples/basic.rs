@@ -9,6 +9,14 @@ const F64_VALUE_CUSTOM: f64 = 0.0;
 #[const_tweaker::tweak]
 const F64_VALUE_DEFAULT: f64 = 0.0;
 
+// Slider for a f32
+#[const_tweaker::tweak(min = 0.0, max = 1.0)]
+const F32_VALUE: f32 = 1.0;
+
+// Number input
+#[const_tweaker::tweak(min = 0, max = 1000)]
+const I32_VALUE: i32 = 100;
+
 // Checkbox
 #[const_tweaker::tweak]
 const BOOL_VALUE: bool = false;
@@ -16,11 +24,17 @@ const BOOL_VALUE: bool = false;
 fn main() -> Result<()> {
     // Run the tweaker server only when in debug mode
     #[cfg(debug_assertions)]
-    const_tweaker::run()?;
+    let _tweaker = const_tweaker::run()?;
 
     // Print the constant value times every second
     loop {
-        dbg!(F64_VALUE_CUSTOM, F64_VALUE_DEFAULT, BOOL_VALUE);
+        dbg!(
+            F64_VALUE_CUSTOM,
+            F64_VALUE_DEFAULT,
+            F32_VALUE,
+            I32_VALUE,
+            BOOL_VALUE
+        );
 
         thread::sleep(Duration::from_secs(1));
     }
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use std::{path::Path, thread, time::Duration};
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const GRAVITY: f64 = 9.8;
+
+#[const_tweaker::tweak]
+const DEBUG_DRAW: bool = false;
+
+fn main() -> Result<()> {
+    // Register the values so they are written to the file
+    dbg!(GRAVITY, DEBUG_DRAW);
+
+    // Create the file with the default values the first time, edit it to change the values
+    let path = Path::new("tweaks.json");
+    if !path.exists() {
+        const_tweaker::save_to_file(path)?;
+    }
+    const_tweaker::load_from_file(path)?;
+
+    // The file is reloaded every time it's saved until the handle is dropped
+    let _watcher = const_tweaker::watch_file(path)?;
+    println!("Edit {} to change the values", path.display());
+
+    loop {
+        println!("gravity: {}, debug draw: {}", *GRAVITY, *DEBUG_DRAW);
+
+        thread::sleep(Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use std::{env, process::Command};
+
+#[const_tweaker::tweak]
+const VALUE: f64 = 0.0;
+
+fn main() -> Result<()> {
+    // Let the operating system pick a free port so multiple instances can run at the same time
+    let tweaker = const_tweaker::run_on_random_port()?;
+    println!("Tweak the values at {}", tweaker.url());
+
+    // Register the value so it shows up in the web GUI
+    dbg!(VALUE);
+
+    // Pass the URL to another program, for example `cargo run --example random_port -- xdg-open`
+    let program = env::args().nth(1).unwrap_or_else(|| "curl".to_string());
+    let status = Command::new(program).arg(tweaker.url()).status()?;
+    println!("Subprocess exited with {}", status);
+
+    tweaker.shutdown()?;
+
+    Ok(())
+}
@@ -1,7 +1,12 @@
 use darling::FromMeta;
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{parse_macro_input, spanned::Spanned, AttributeArgs, Error, Expr, ItemConst, Type};
+use proc_macro2::Span;
+use quote::{format_ident, quote, ToTokens};
+use std::{fmt::Display, str::FromStr};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, AttributeArgs, Error, Expr, Item, ItemConst,
+    Lit, Meta, MetaNameValue, Path, Token, Type,
+};
 
 type TokenStream2 = proc_macro2::TokenStream;
 
@@ -9,11 +14,25 @@ type TokenStream2 = proc_macro2::TokenStream;
 #[derive(Debug, FromMeta)]
 struct Metadata {
     #[darling(default)]
-    min: Option<f64>,
+    min: Option<Lit>,
     #[darling(default)]
-    max: Option<f64>,
+    max: Option<Lit>,
     #[darling(default)]
-    step: Option<f64>,
+    step: Option<Lit>,
+    #[darling(default)]
+    max_length: Option<Lit>,
+    #[darling(default)]
+    on_change: Option<Path>,
+    #[darling(default)]
+    group: Option<String>,
+    #[darling(default)]
+    label: Option<String>,
+    #[darling(default)]
+    hidden: bool,
+    #[darling(default)]
+    readonly: bool,
+    #[darling(default)]
+    scale: Option<String>,
 }
 
 impl Metadata {
@@ -23,6 +42,141 @@ impl Metadata {
             Err(e) => Err(TokenStream::from(e.write_errors())),
         }
     }
+
+    /// Whether the slider uses a logarithmic scale, only `"linear"` & `"log"` are valid scales.
+    pub fn log_scale(&self) -> Result<bool, TokenStream> {
+        match self.scale.as_deref() {
+            None | Some("linear") => Ok(false),
+            Some("log") => Ok(true),
+            Some(scale) => Err(TokenStream::from(
+                Error::new(
+                    Span::call_site(),
+                    format!("expected scale \"linear\" or \"log\", not \"{}\"", scale),
+                )
+                .to_compile_error(),
+            )),
+        }
+    }
+
+    /// The group as an `Option<&'static str>` expression.
+    pub fn group_tokens(&self) -> TokenStream2 {
+        match &self.group {
+            Some(group) => quote! { Some(#group) },
+            None => quote! { None },
+        }
+    }
+
+    /// The label as an `Option<&'static str>` expression.
+    pub fn label_tokens(&self) -> TokenStream2 {
+        match &self.label {
+            Some(label) => quote! { Some(#label) },
+            None => quote! { None },
+        }
+    }
+}
+
+/// The doc comment of the value as an `Option<&'static str>` expression.
+fn description_tokens(attrs: &[Attribute]) -> TokenStream2 {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(line),
+                ..
+            })) if path.is_ident("doc") => Some(line.value().trim().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        quote! { None }
+    } else {
+        let description = lines.join("\n");
+        quote! { Some(#description) }
+    }
+}
+
+/// The doc comments, which are moved to the generated static.
+fn doc_attributes(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .collect()
+}
+
+/// Parse an optional literal from the metadata as the type of the field.
+fn parse_lit<T>(lit: &Option<Lit>) -> Result<Option<T>, TokenStream>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = match lit {
+        None => return Ok(None),
+        Some(Lit::Int(int)) => int.base10_parse::<T>(),
+        Some(Lit::Float(float)) => float.base10_parse::<T>(),
+        // Negative numbers can't be passed as literals in attributes, so allow them as strings
+        Some(Lit::Str(string)) => string
+            .value()
+            .parse::<T>()
+            .map_err(|err| Error::new(string.span(), err)),
+        Some(lit) => Err(Error::new(lit.span(), "expected a number")),
+    };
+
+    match value {
+        Ok(value) => Ok(Some(value)),
+        Err(err) => Err(TokenStream::from(err.to_compile_error())),
+    }
+}
+
+/// Parse an optional literal from the metadata as the type of the field.
+///
+/// When the literal is not supplied the fallback tokens are used instead.
+fn lit_or<T>(lit: &Option<Lit>, fallback: TokenStream2) -> Result<TokenStream2, TokenStream>
+where
+    T: FromStr + ToTokens,
+    T::Err: Display,
+{
+    match parse_lit::<T>(lit)? {
+        Some(value) => Ok(quote! { #value }),
+        None => Ok(fallback),
+    }
+}
+
+/// Parse the optional step literal, which must be larger than zero.
+///
+/// When the literal is not supplied the fallback tokens are used instead.
+fn step_or<T>(lit: &Option<Lit>, fallback: TokenStream2) -> Result<TokenStream2, TokenStream>
+where
+    T: FromStr + ToTokens + PartialOrd + Default,
+    T::Err: Display,
+{
+    match parse_lit::<T>(lit)? {
+        Some(value) if value <= T::default() => Err(TokenStream::from(
+            Error::new(lit.span(), "step must be larger than zero").to_compile_error(),
+        )),
+        Some(value) => Ok(quote! { #value }),
+        None => Ok(fallback),
+    }
+}
+
+/// Check that the minimum of a logarithmic scale is larger than zero, the logarithm of zero &
+/// negative numbers isn't defined.
+fn check_log_min<T>(lit: &Option<Lit>) -> Result<(), TokenStream>
+where
+    T: FromStr + PartialOrd + Default,
+    T::Err: Display,
+{
+    match parse_lit::<T>(lit)? {
+        Some(min) if min <= T::default() => Err(TokenStream::from(
+            Error::new(
+                lit.span(),
+                "min must be larger than zero for a logarithmic scale",
+            )
+            .to_compile_error(),
+        )),
+        _ => Ok(()),
+    }
 }
 
 /// Convert a given type to a const_tweaker Field with metadata.
@@ -31,50 +185,270 @@ fn field_init(
     metadata: Metadata,
     default_value: Expr,
 ) -> Result<TokenStream2, TokenStream> {
-    if let Type::Path(type_path) = &*ty {
+    if let Type::Path(type_path) = ty {
         match type_path.path.get_ident() {
-            Some(type_ident) => {
-                let min = metadata.min.unwrap_or(-1.0);
-                let max = metadata.max.unwrap_or(1.0);
-                let step = metadata.step.unwrap_or(0.1);
-
-                match &*(type_ident.to_string()) {
-                    "f64" => Ok(quote! {
-                        const_tweaker::Field::F64 {
+            Some(type_ident) => match &*(type_ident.to_string()) {
+                "f64" => {
+                    // Read-only values have no slider, so they aren't bounded by default, the other
+                    // default bounds are widened so the default value is always allowed
+                    let (min, max) = if metadata.readonly {
+                        (quote! { std::f64::MIN }, quote! { std::f64::MAX })
+                    } else if metadata.log_scale()? {
+                        check_log_min::<f64>(&metadata.min)?;
+                        (
+                            quote! { if value > 0.0 { f64::min(0.001, value) } else { 0.001 } },
+                            quote! { f64::max(1000.0, value) },
+                        )
+                    } else {
+                        (
+                            quote! { f64::min(-100.0, value) },
+                            quote! { f64::max(100.0, value) },
+                        )
+                    };
+                    let min = lit_or::<f64>(&metadata.min, min)?;
+                    let max = lit_or::<f64>(&metadata.max, max)?;
+                    let step = step_or::<f64>(&metadata.step, quote! { 0.1 })?;
+
+                    Ok(quote! {
+                        {
+                            let value: f64 = #default_value;
+                            const_tweaker::Field::F64 {
+                                value,
+                                min: #min,
+                                max: #max,
+                                step: #step,
+                            }
+                        }
+                    })
+                }
+                "f32" => {
+                    // The default bounds are widened so the default value is always allowed
+                    let (min, max) = if metadata.readonly {
+                        (quote! { std::f32::MIN }, quote! { std::f32::MAX })
+                    } else if metadata.log_scale()? {
+                        check_log_min::<f32>(&metadata.min)?;
+                        (
+                            quote! { if value > 0.0 { f32::min(0.001, value) } else { 0.001 } },
+                            quote! { f32::max(1000.0, value) },
+                        )
+                    } else {
+                        (
+                            quote! { f32::min(-100.0, value) },
+                            quote! { f32::max(100.0, value) },
+                        )
+                    };
+                    let min = lit_or::<f32>(&metadata.min, min)?;
+                    let max = lit_or::<f32>(&metadata.max, max)?;
+                    let step = step_or::<f32>(&metadata.step, quote! { 0.1 })?;
+
+                    Ok(quote! {
+                        {
+                            let value: f32 = #default_value;
+                            const_tweaker::Field::F32 {
+                                value,
+                                min: #min,
+                                max: #max,
+                                step: #step,
+                            }
+                        }
+                    })
+                }
+                "i32" => {
+                    let min = lit_or::<i32>(&metadata.min, quote! { std::i32::MIN })?;
+                    let max = lit_or::<i32>(&metadata.max, quote! { std::i32::MAX })?;
+                    let step = step_or::<i32>(&metadata.step, quote! { 1 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::I32 {
                             value: #default_value,
                             min: #min,
                             max: #max,
                             step: #step,
                         }
-                    }),
-                    "bool" => Ok(quote! {
-                        const_tweaker::Field::Bool {
+                    })
+                }
+                "u32" => {
+                    let min = lit_or::<u32>(&metadata.min, quote! { 0 })?;
+                    let max = lit_or::<u32>(&metadata.max, quote! { std::u32::MAX })?;
+                    let step = step_or::<u32>(&metadata.step, quote! { 1 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::U32 {
                             value: #default_value,
+                            min: #min,
+                            max: #max,
+                            step: #step,
                         }
-                    }),
-                    _ => mismatching_type_error(&ty),
+                    })
                 }
-            }
-            None => mismatching_type_error(&ty),
+                "usize" => {
+                    let min = lit_or::<usize>(&metadata.min, quote! { 0 })?;
+                    let max = lit_or::<usize>(&metadata.max, quote! { std::usize::MAX })?;
+                    let step = step_or::<usize>(&metadata.step, quote! { 1 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::Usize {
+                            value: #default_value,
+                            min: #min,
+                            max: #max,
+                            step: #step,
+                        }
+                    })
+                }
+                "u8" => {
+                    let min = lit_or::<u8>(&metadata.min, quote! { 0 })?;
+                    let max = lit_or::<u8>(&metadata.max, quote! { 255 })?;
+                    let step = step_or::<u8>(&metadata.step, quote! { 1 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::U8 {
+                            value: #default_value,
+                            min: #min,
+                            max: #max,
+                            step: #step,
+                        }
+                    })
+                }
+                "u16" => {
+                    let min = lit_or::<u16>(&metadata.min, quote! { std::u16::MIN })?;
+                    let max = lit_or::<u16>(&metadata.max, quote! { std::u16::MAX })?;
+                    let step = step_or::<u16>(&metadata.step, quote! { 1 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::U16 {
+                            value: #default_value,
+                            min: #min,
+                            max: #max,
+                            step: #step,
+                        }
+                    })
+                }
+                "i8" => {
+                    let min = lit_or::<i8>(&metadata.min, quote! { std::i8::MIN })?;
+                    let max = lit_or::<i8>(&metadata.max, quote! { std::i8::MAX })?;
+                    let step = step_or::<i8>(&metadata.step, quote! { 1 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::I8 {
+                            value: #default_value,
+                            min: #min,
+                            max: #max,
+                            step: #step,
+                        }
+                    })
+                }
+                "i16" => {
+                    let min = lit_or::<i16>(&metadata.min, quote! { std::i16::MIN })?;
+                    let max = lit_or::<i16>(&metadata.max, quote! { std::i16::MAX })?;
+                    let step = step_or::<i16>(&metadata.step, quote! { 1 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::I16 {
+                            value: #default_value,
+                            min: #min,
+                            max: #max,
+                            step: #step,
+                        }
+                    })
+                }
+                "i64" => Ok(quote! {
+                    const_tweaker::Field::I64 {
+                        value: #default_value,
+                    }
+                }),
+                "u128" => Ok(quote! {
+                    const_tweaker::Field::U128 {
+                        value: #default_value,
+                    }
+                }),
+                "i128" => Ok(quote! {
+                    const_tweaker::Field::I128 {
+                        value: #default_value,
+                    }
+                }),
+                "String" => {
+                    let max_length = lit_or::<usize>(&metadata.max_length, quote! { 4096 })?;
+
+                    Ok(quote! {
+                        const_tweaker::Field::String {
+                            // Allow string literals to be used as the default value
+                            value: std::string::String::from(#default_value),
+                            max_length: #max_length,
+                        }
+                    })
+                }
+                "bool" => Ok(quote! {
+                    const_tweaker::Field::Bool {
+                        value: #default_value,
+                    }
+                }),
+                _ => mismatching_type_error(ty),
+            },
+            None => mismatching_type_error(ty),
         }
     } else {
-        mismatching_type_error(&ty)
+        mismatching_type_error(ty)
     }
 }
 
 /// Convert a given type to a const_tweaker Field type.
 fn field_name(ty: &Type) -> Result<TokenStream2, TokenStream> {
-    if let Type::Path(type_path) = &*ty {
+    if let Type::Path(type_path) = ty {
         match type_path.path.get_ident() {
             Some(type_ident) => match &*(type_ident.to_string()) {
                 "f64" => Ok(quote! { const_tweaker::Field::F64 }),
+                "f32" => Ok(quote! { const_tweaker::Field::F32 }),
+                "i32" => Ok(quote! { const_tweaker::Field::I32 }),
+                "u32" => Ok(quote! { const_tweaker::Field::U32 }),
+                "usize" => Ok(quote! { const_tweaker::Field::Usize }),
+                "u8" => Ok(quote! { const_tweaker::Field::U8 }),
+                "u16" => Ok(quote! { const_tweaker::Field::U16 }),
+                "i8" => Ok(quote! { const_tweaker::Field::I8 }),
+                "i16" => Ok(quote! { const_tweaker::Field::I16 }),
+                "i64" => Ok(quote! { const_tweaker::Field::I64 }),
+                "u128" => Ok(quote! { const_tweaker::Field::U128 }),
+                "i128" => Ok(quote! { const_tweaker::Field::I128 }),
+                "String" => Ok(quote! { const_tweaker::Field::String }),
                 "bool" => Ok(quote! { const_tweaker::Field::Bool }),
-                _ => mismatching_type_error(&ty),
+                _ => mismatching_type_error(ty),
             },
-            None => mismatching_type_error(&ty),
+            None => mismatching_type_error(ty),
         }
     } else {
-        mismatching_type_error(&ty)
+        mismatching_type_error(ty)
+    }
+}
+
+/// Whether the field of the type has a minimum & maximum.
+fn has_bounds(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => [
+            "f64", "f32", "i32", "u32", "usize", "u8", "u16", "i8", "i16",
+        ]
+        .iter()
+        .any(|name| type_path.path.is_ident(name)),
+        _ => false,
+    }
+}
+
+/// Whether the type is not one of the supported primitive types, so it must implement the
+/// `Tweakable` trait.
+fn is_custom(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => field_name(ty).is_err() && type_path.qself.is_none(),
+        // Tuples like `(f64, f64)`
+        Type::Tuple(tuple) => !tuple.elems.is_empty(),
+        // Arrays like `[f32; 4]`
+        Type::Array(_) => true,
+        _ => false,
+    }
+}
+
+/// Whether the type is a `String`, which can't be dereferenced like the primitive types.
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path.path.is_ident("String"),
+        _ => false,
     }
 }
 
@@ -83,19 +457,58 @@ fn mismatching_type_error<T>(ty: &Type) -> Result<T, TokenStream> {
     Err(TokenStream::from(
         Error::new(
             ty.span(),
-            "expected bool or f64, other types are not supported in const_tweaker (yet)",
+            "expected bool, f64, f32, i8, i16, i32, u32, usize, u8, u16, i64, u128, i128, String, (f64, f64), (f64, f64, f64), [f32; 4] or a type implementing const_tweaker::Tweakable",
         )
         .to_compile_error(),
     ))
 }
 
-/// Proc macro call but with a result, which allows the use of `?`.
-fn tweak_impl(args: AttributeArgs, input: ItemConst) -> Result<TokenStream, TokenStream> {
+/// Proc macro call for a type implementing the `Tweakable` trait.
+fn tweak_custom_impl(metadata: Metadata, input: ItemConst) -> Result<TokenStream, TokenStream> {
+    // Custom types render their own widgets, so the options of the built-in widgets don't apply
+    let unsupported = [
+        ("min", &metadata.min),
+        ("max", &metadata.max),
+        ("step", &metadata.step),
+        ("max_length", &metadata.max_length),
+    ];
+    if let Some((option, Some(lit))) = unsupported.iter().find(|(_, lit)| lit.is_some()) {
+        return Err(TokenStream::from(
+            Error::new(
+                lit.span(),
+                format!("{} is not supported for custom types", option),
+            )
+            .to_compile_error(),
+        ));
+    }
+
     let name = input.ident;
     let init_name = format_ident!("{}_INIT", name);
     let ty = input.ty;
-    let field_init = field_init(&*ty, Metadata::from_attributes(args)?, *input.expr)?;
-    let field_name = field_name(&*ty)?;
+    let default_value = input.expr;
+    let description = description_tokens(&input.attrs);
+    let docs = doc_attributes(&input.attrs);
+    let group = metadata.group_tokens();
+    let label = metadata.label_tokens();
+    let hidden = metadata.hidden;
+    let readonly = metadata.readonly;
+
+    // Register the callback which converts the value from the field
+    let register_callback = metadata.on_change.map(|on_change| {
+        quote! {
+            const_tweaker::CALLBACKS
+                .entry(stringify!(#name))
+                .or_insert_with(Vec::new)
+                .push(std::sync::Arc::new(|field| match field {
+                    const_tweaker::Field::Custom { ref value, .. } => {
+                        if let Ok(value) = <#ty as const_tweaker::Tweakable>::from_json(value.clone()) {
+                            #on_change(value);
+                        }
+                    }
+                    _ => panic!("Type mismatch, this probably means there's a duplicate value in the map, please report an issue")
+                }));
+        }
+    });
 
     let result = quote! {
         #[allow(non_camel_case_types)]
@@ -106,61 +519,347 @@ fn tweak_impl(args: AttributeArgs, input: ItemConst) -> Result<TokenStream, Toke
         }
 
         impl #name {
-            pub fn get(&self) -> &'static #ty {
-                // Insert the default value only the first time
+            // The value can be changed in the meantime, so it's cloned instead of referenced
+            pub fn get(&self) -> #ty {
+                // Values mocked in a test take precedence
+                if let Some(value) = const_tweaker::mocked_value::<#ty>(stringify!(#name)) {
+                    return value;
+                }
+
                 #init_name.call_once(|| {
-                    const_tweaker::DATA.insert(stringify!(#name), #field_init);
+                    const_tweaker::register_tweakable::<#ty>(stringify!(#name), #default_value);
+                    // The entry is created by the library, so the metadata is set afterwards
+                    if let Some(mut entry) = const_tweaker::DATA.get_mut(stringify!(#name)) {
+                        entry.description = #description;
+                        entry.group = #group;
+                        entry.label = #label;
+                        entry.file = Some(file!());
+                        entry.hidden = #hidden;
+                        entry.readonly = #readonly;
+                    }
+                    #register_callback
                 });
 
-                // Retrieve the value from the datastore and unwrap it
-                match const_tweaker::DATA.get(stringify!(#name)).expect("Value should have been added already").value() {
-                    #field_name { ref value, .. } => unsafe {
-                        // Make the reference static, so it leaks, but that shouldn't matter
-                        // because there will always be one reference since the dashmap is global
-                        std::mem::transmute::<&#ty, &'static #ty>(value)
-                    },
+                let value = const_tweaker::get_tweakable::<#ty>(stringify!(#name))
+                    .expect("Type mismatch, this probably means there's a duplicate value in the map, please report an issue");
+                const_tweaker::check_assertions(stringify!(#name), &value, |value| {
+                    <#ty as const_tweaker::Tweakable>::to_json(value).to_string()
+                });
+
+                value
+            }
+
+            /// Add an invariant checked on every read, used by `tweak_assert!`.
+            #[doc(hidden)]
+            pub fn assert_with(&self, message: String, check: impl Fn(&#ty) -> bool + Send + Sync + 'static) {
+                const_tweaker::register_assertion(stringify!(#name), message, check);
+            }
+
+            /// Override the value on the current thread, used by `mock_tweaks!` which passes the
+            /// function so this doesn't depend on the `testing` feature.
+            #[doc(hidden)]
+            pub fn mock<G>(&self, value: #ty, mock: fn(&'static str, #ty) -> G) -> G {
+                mock(stringify!(#name), value)
+            }
+
+            /// Change the value, returns `false` when it's locked or rejected by the type.
+            pub fn set(&self, value: #ty) -> bool {
+                // Register the value first
+                self.get();
+
+                let json = <#ty as const_tweaker::Tweakable>::to_json(&value);
+                const_tweaker::set_with(stringify!(#name), move |field| match field {
+                    const_tweaker::Field::Custom { ref mut value, .. } => *value = json,
                     _ => panic!("Type mismatch, this probably means there's a duplicate value in the map, please report an issue")
-                }
+                })
             }
         }
 
-        // Automatically unwrap the primitive value from the struct when dereferencing
-        impl std::ops::Deref for #name {
-            type Target = #ty;
+        // The setting of the field in the map is only done once
+        static #init_name: std::sync::Once = std::sync::Once::new();
+        // A static variable is created as an instance of the above defined struct
+        #(#docs)*
+        static #name: #name = #name { __private_field: () };
+    };
+
+    Ok(result.into())
+}
 
-            fn deref(&self) -> &'static #ty {
-                self.get()
+/// Proc macro call but with a result, which allows the use of `?`.
+fn tweak_impl(metadata: Metadata, input: ItemConst) -> Result<TokenStream, TokenStream> {
+    let log_scale = metadata.log_scale()?;
+    let is_float = match &*input.ty {
+        Type::Path(type_path) => type_path.path.is_ident("f64") || type_path.path.is_ident("f32"),
+        _ => false,
+    };
+    if log_scale && !is_float {
+        return Err(TokenStream::from(
+            Error::new(
+                input.ty.span(),
+                "a logarithmic scale is only supported for f64 & f32",
+            )
+            .to_compile_error(),
+        ));
+    }
+
+    if is_custom(&input.ty) {
+        return tweak_custom_impl(metadata, input);
+    }
+
+    let name = input.ident;
+    let init_name = format_ident!("{}_INIT", name);
+    let static_name = format_ident!("{}_STATIC", name);
+    let ty = input.ty;
+    let on_change = metadata.on_change.clone();
+    let group = metadata.group_tokens();
+    let label = metadata.label_tokens();
+    let hidden = metadata.hidden;
+    let readonly = metadata.readonly;
+    let description = description_tokens(&input.attrs);
+    let docs = doc_attributes(&input.attrs);
+    let bounded = has_bounds(&ty) && (metadata.min.is_some() || metadata.max.is_some());
+    let field_init = field_init(&ty, metadata, *input.expr)?;
+    let field_name = field_name(&ty)?;
+
+    // Register the callback which unwraps the value from the field
+    let register_callback = on_change.map(|on_change| {
+        quote! {
+            const_tweaker::CALLBACKS
+                .entry(stringify!(#name))
+                .or_insert_with(Vec::new)
+                .push(std::sync::Arc::new(|field| match field {
+                    #field_name { ref value, .. } => #on_change(value.clone()),
+                    _ => panic!("Type mismatch, this probably means there's a duplicate value in the map, please report an issue")
+                }));
+        }
+    });
+
+    // Insert the default value only the first time and retrieve the field from the datastore
+    let field = quote! {
+        #init_name.call_once(|| {
+            const_tweaker::register_value(stringify!(#name), const_tweaker::TweakEntry {
+                description: #description,
+                group: #group,
+                label: #label,
+                file: Some(file!()),
+                hidden: #hidden,
+                readonly: #readonly,
+                log_scale: #log_scale,
+                ..const_tweaker::TweakEntry::new(#field_init)
+            });
+            #register_callback
+        });
+
+        let entry = const_tweaker::DATA.get(stringify!(#name)).expect("Value should have been added already");
+    };
+
+    // Values with explicit bounds are clamped when read, so a default outside of them isn't used
+    let unwrap_value = if bounded {
+        quote! { #field_name { ref value, min, max, .. } => const_tweaker::clamp(*value, min, max), }
+    } else {
+        quote! { #field_name { ref value, .. } => *value, }
+    };
+
+    let accessors = if is_string(&ty) {
+        // A reference to the string can't be held because it might be changed in the meantime,
+        // so it's cloned instead
+        quote! {
+            impl #name {
+                pub fn get(&self) -> String {
+                    // Values mocked in a test take precedence
+                    if let Some(value) = const_tweaker::mocked_value::<String>(stringify!(#name)) {
+                        return value;
+                    }
+
+                    #field
+
+                    let value = match entry.value().value {
+                        #field_name { ref value, .. } => value.clone(),
+                        _ => panic!("Type mismatch, this probably means there's a duplicate value in the map, please report an issue")
+                    };
+                    // The entry has to be released before an assertion can panic
+                    drop(entry);
+                    const_tweaker::check_assertions(stringify!(#name), &value, |value| format!("{:?}", value));
+
+                    value
+                }
+            }
+
+            impl std::fmt::Debug for #name {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "{:?}", self.get())
+                }
+            }
+
+            impl std::fmt::Display for #name {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "{}", self.get())
+                }
             }
         }
+    } else {
+        quote! {
+            impl #name {
+                pub fn get(&self) -> &'static #ty {
+                    // Values mocked in a test take precedence
+                    if let Some(value) = const_tweaker::mocked_value::<#ty>(stringify!(#name)) {
+                        return #static_name.get(value);
+                    }
 
-        impl std::fmt::Debug for #name {
-            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                write!(f, "{:?}", self.get())
+                    #field
+
+                    // Unwrap the value from the field, the reference into the map can't be
+                    // returned because the value can be changed while it's held
+                    let value = match entry.value().value {
+                        #unwrap_value
+                        _ => panic!("Type mismatch, this probably means there's a duplicate value in the map, please report an issue")
+                    };
+                    // The entry has to be released before an assertion can panic
+                    drop(entry);
+                    const_tweaker::check_assertions(stringify!(#name), &value, |value| format!("{:?}", value));
+
+                    #static_name.get(value)
+                }
             }
+
+            // The references handed out by `get` & `Deref`
+            static #static_name: const_tweaker::StaticValue<#ty> = const_tweaker::StaticValue::new();
+
+            // Automatically unwrap the primitive value from the struct when dereferencing
+            impl std::ops::Deref for #name {
+                type Target = #ty;
+
+                fn deref(&self) -> &'static #ty {
+                    self.get()
+                }
+            }
+
+            impl std::fmt::Debug for #name {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "{:?}", self.get())
+                }
+            }
+
+            impl std::fmt::Display for #name {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "{:?}", self.get())
+                }
+            }
+        }
+    };
+
+    let result = quote! {
+        #[allow(non_camel_case_types)]
+        #[doc(hidden)]
+        #[derive(Copy, Clone)]
+        pub struct #name {
+            __private_field: ()
         }
 
-        impl std::fmt::Display for #name {
-            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                write!(f, "{:?}", self.get())
+        #accessors
+
+        impl #name {
+            /// Override the value on the current thread, used by `mock_tweaks!` which passes the
+            /// function so this doesn't depend on the `testing` feature.
+            #[doc(hidden)]
+            pub fn mock<G>(&self, value: #ty, mock: fn(&'static str, #ty) -> G) -> G {
+                mock(stringify!(#name), value)
+            }
+
+            /// Add an invariant checked on every read, used by `tweak_assert!`.
+            #[doc(hidden)]
+            pub fn assert_with(&self, message: String, check: impl Fn(&#ty) -> bool + Send + Sync + 'static) {
+                const_tweaker::register_assertion(stringify!(#name), message, check);
+            }
+
+            /// Change the value, returns `false` when it's locked or outside of the bounds.
+            pub fn set(&self, value: #ty) -> bool {
+                #field
+                // The entry has to be released before the value can be changed
+                drop(entry);
+
+                const_tweaker::set_with(stringify!(#name), move |field| match field {
+                    #field_name { value: ref mut current, .. } => *current = value,
+                    _ => panic!("Type mismatch, this probably means there's a duplicate value in the map, please report an issue")
+                })
             }
         }
 
         // The setting of the field in the map is only done once
         static #init_name: std::sync::Once = std::sync::Once::new();
         // A static variable is created as an instance of the above defined struct
+        #(#docs)*
         static #name: #name = #name { __private_field: () };
     };
 
     Ok(result.into())
 }
 
+/// Parse the item the attribute is put on, a `static` item is converted to the same `const` item.
+fn parse_item(input: TokenStream) -> Result<ItemConst, TokenStream> {
+    let error = |span: Span, message: &str| {
+        Err(TokenStream::from(
+            Error::new(span, message).to_compile_error(),
+        ))
+    };
+
+    match syn::parse::<Item>(input) {
+        Ok(Item::Const(item)) => Ok(item),
+        Ok(Item::Static(item)) => match item.mutability {
+            Some(mutability) => error(
+                mutability.span(),
+                "a tweaked value can't be `static mut`, it's changed from the web GUI",
+            ),
+            None => Ok(ItemConst {
+                attrs: item.attrs,
+                vis: item.vis,
+                const_token: Token![const](item.static_token.span),
+                ident: item.ident,
+                colon_token: item.colon_token,
+                ty: item.ty,
+                eq_token: item.eq_token,
+                expr: item.expr,
+                semi_token: item.semi_token,
+            }),
+        },
+        Ok(item) => error(item.span(), "expected a `const` or `static` item"),
+        Err(err) => Err(TokenStream::from(err.to_compile_error())),
+    }
+}
+
 /// Expose a const variable to the web GUI so it can be changed from a live setting.
+///
+/// The attribute can be put on `const` & `static` items.
 #[proc_macro_attribute]
 pub fn tweak(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as AttributeArgs);
-    let input = parse_macro_input!(input as ItemConst);
+    let input = match parse_item(input) {
+        Ok(input) => input,
+        Err(err) => return err,
+    };
+
+    match Metadata::from_attributes(args).and_then(|metadata| tweak_impl(metadata, input)) {
+        Ok(result) => result,
+        Err(err) => err,
+    }
+}
+
+/// Show a variable that's changed by the program in the web GUI, it can't be changed there.
+///
+/// This is the same as `tweak` with the `readonly` option.
+#[proc_macro_attribute]
+pub fn monitor(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let input = match parse_item(input) {
+        Ok(input) => input,
+        Err(err) => return err,
+    };
 
-    match tweak_impl(args, input) {
+    let metadata = Metadata::from_attributes(args).map(|metadata| Metadata {
+        readonly: true,
+        ..metadata
+    });
+    match metadata.and_then(|metadata| tweak_impl(metadata, input)) {
         Ok(result) => result,
         Err(err) => err,
     }
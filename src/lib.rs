@@ -2,7 +2,7 @@
 //!
 //! This library starts a web server at `http://127.0.0.1:9938` where you can change the values of `const` variables in your crate.
 //!
-//! `f64` & `bool` are the types that are currently supported.
+//! `f64`, `f32`, `i8`, `i16`, `i32`, `u32`, `usize`, `u8`, `u16`, `i64`, `u128`, `i128`, `String`, `bool`, `(f64, f64)`, `(f64, f64, f64)`, `[f32; 4]`, `Option<f64>`, `Option<bool>`, `Duration` & `Vec<f64>` are the types that are currently supported.
 //!
 //! ## Example
 //! ```rust
@@ -14,7 +14,7 @@
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     // Initialize the server at 'http://127.0.0.1:9938' when running in debug mode
 //!     #[cfg(debug_assertions)]
-//!     const_tweaker::run()?;
+//!     let _tweaker = const_tweaker::run()?;
 //!
 //!     // Enter a GUI/Game loop
 //!     loop {
@@ -33,7 +33,7 @@
 //!
 //! `f64`:
 //! ```rust
-//! // Spawns a slider
+//! // Spawns a slider from -100-100, the bounds are widened when the value is outside of them
 //! #[const_tweaker::tweak]
 //! const DEFAULT_VALUE: f64 = 0.0;
 //!
@@ -42,26 +42,375 @@
 //! const CUSTOM_VALUE: f64 = 0.0;
 //! ```
 //!
+//! Values outside of the `min` & `max` bounds are rejected by the web server & the `set`
+//! functions, this holds for all types that support them. When the bounds are passed explicitly,
+//! reading the value also clamps it to the bounds, so a default value outside of them is never
+//! returned:
+//! ```rust
+//! #[const_tweaker::tweak(min = 0.0, max = 20.0)]
+//! const GRAVITY: f64 = 50.0;
+//!
+//! assert_eq!(*GRAVITY, 20.0);
+//! ```
+//!
+//! The attribute can also be put on `static` items, but not on `static mut` items because the
+//! value is changed from the web GUI:
+//! ```rust
+//! #[const_tweaker::tweak(min = 0.0, max = 100.0, group = "Physics")]
+//! static GRAVITY: f64 = 9.8;
+//!
+//! assert_eq!(*GRAVITY, 9.8);
+//! ```
+//! ```compile_fail
+//! #[const_tweaker::tweak]
+//! static mut GRAVITY: f64 = 9.8;
+//! ```
+//!
+//! The `step` has to be larger than zero:
+//! ```compile_fail
+//! #[const_tweaker::tweak(step = 0.0)]
+//! const INVALID_STEP: f64 = 0.0;
+//! ```
+//!
+//! Values spanning multiple orders of magnitude can use a slider on a logarithmic scale, the
+//! bounds default to `0.001` & `1000.0`, widened when the value is outside of them:
+//! ```rust
+//! #[const_tweaker::tweak(min = 0.001, max = 1000.0, scale = "log")]
+//! const SCALE: f64 = 1.0;
+//! ```
+//!
+//! The minimum of a logarithmic scale has to be larger than zero:
+//! ```compile_fail
+//! #[const_tweaker::tweak(min = 0.0, scale = "log")]
+//! const INVALID_SCALE: f64 = 1.0;
+//! ```
+//!
+//! `f32`:
+//! ```rust
+//! // Spawns a slider, the same options as `f64` are supported
+//! #[const_tweaker::tweak(min = 0.0, max = 1.0)]
+//! const OPACITY: f32 = 1.0;
+//! ```
+//!
+//! `i8` & `i16`:
+//! ```rust
+//! // Spawns a slider from -128 to 127
+//! #[const_tweaker::tweak]
+//! const SAMPLE_I8: i8 = 0;
+//!
+//! // Spawns a slider from -32768 to 32767
+//! #[const_tweaker::tweak]
+//! const SAMPLE_I16: i16 = 0;
+//! ```
+//!
+//! `i32`:
+//! ```rust
+//! // Spawns a number input
+//! #[const_tweaker::tweak]
+//! const DEFAULT_VALUE: i32 = 0;
+//!
+//! // Spawns a number input that only allows values from 0-100 in steps of 5
+//! #[const_tweaker::tweak(min = 0, max = 100, step = 5)]
+//! const CUSTOM_VALUE: i32 = 0;
+//!
+//! // Negative bounds have to be passed as strings
+//! #[const_tweaker::tweak(min = "-10", max = 10)]
+//! const NEGATIVE_VALUE: i32 = 0;
+//! ```
+//!
+//! `u32`:
+//! ```rust
+//! // Spawns a number input that can't go below zero
+//! #[const_tweaker::tweak]
+//! const DEFAULT_VALUE: u32 = 0;
+//!
+//! // Spawns a number input that only allows values from 1-8
+//! #[const_tweaker::tweak(min = 1, max = 8)]
+//! const CUSTOM_VALUE: u32 = 1;
+//! ```
+//!
+//! `usize`:
+//! ```rust
+//! // Spawns a number input that can't go below zero
+//! #[const_tweaker::tweak]
+//! const CHUNK_SIZE: usize = 64;
+//! ```
+//!
+//! The maximum of a `usize` depends on the platform, on 32-bit targets values above `u32::MAX`
+//! are rejected by the web server.
+//!
+//! `u8`:
+//! ```rust
+//! // Spawns a slider from 0-255 showing the value both in decimal & hexadecimal
+//! #[const_tweaker::tweak]
+//! const ALPHA: u8 = 200;
+//! ```
+//!
+//! `u16`:
+//! ```rust
+//! // Spawns a number input from 0-65535
+//! #[const_tweaker::tweak]
+//! const PORT: u16 = 8080;
+//! ```
+//!
+//! `i64`:
+//! ```rust
+//! // Spawns a text input, the value is sent as a string so no precision is lost
+//! #[const_tweaker::tweak]
+//! const TIMESTAMP_OFFSET: i64 = 0;
+//! ```
+//!
+//! `u128` & `i128`:
+//! ```rust
+//! // Spawns text inputs, like `i64` the values are sent as strings
+//! #[const_tweaker::tweak]
+//! const HASH: u128 = 0;
+//!
+//! #[const_tweaker::tweak]
+//! const NONCE: i128 = 0;
+//! ```
+//!
+//! `String`:
+//! ```rust
+//! // Spawns a text input, the default value can also be a string literal
+//! #[const_tweaker::tweak]
+//! const ASSET_PATH: String = "assets/player.png";
+//!
+//! // Spawns a text input that doesn't accept strings longer than 16 bytes, the default is 4096
+//! #[const_tweaker::tweak(max_length = 16)]
+//! const PLAYER_NAME: String = "Player";
+//!
+//! // `get()` returns a clone of the string instead of a reference
+//! let asset_path: String = ASSET_PATH.get();
+//! ```
+//!
 //! `bool`:
 //! ```rust
 //! // Spawns a checkbox
 //! #[const_tweaker::tweak]
 //! const DEFAULT_VALUE: bool = true;
 //! ```
+//!
+//! `(f64, f64)` & `(f64, f64, f64)`:
+//! ```rust
+//! // Spawns the number inputs `SPAWN_POINT::x` & `SPAWN_POINT::y`
+//! #[const_tweaker::tweak]
+//! const SPAWN_POINT: (f64, f64) = (0.0, 0.0);
+//!
+//! // Spawns three number inputs, also for `z`
+//! #[const_tweaker::tweak]
+//! const CAMERA_POSITION: (f64, f64, f64) = (0.0, 10.0, -5.0);
+//!
+//! // Like other custom types the tuples are copied when read
+//! let (x, y) = SPAWN_POINT.get();
+//! ```
+//!
+//! `[f32; 4]`:
+//! ```rust
+//! // Spawns a color picker for the red, green & blue channels and a slider for the alpha
+//! #[const_tweaker::tweak]
+//! const CLEAR_COLOR: [f32; 4] = [0.1, 0.2, 0.3, 1.0];
+//!
+//! let [r, g, b, a] = CLEAR_COLOR.get();
+//! ```
+//!
+//! The channels are converted from the hexadecimal color of the picker without any color space
+//! conversion, converting between sRGB & linear colors is up to you.
+//!
+//! A function can be called with the new value every time a value is changed or reset:
+//! ```rust
+//! fn reset_gravity(gravity: f64) {
+//!     println!("Gravity is now {}", gravity);
+//! }
+//!
+//! #[const_tweaker::tweak(min = 0.0, max = 20.0, on_change = "reset_gravity")]
+//! const GRAVITY: f64 = 9.8;
+//! ```
+//!
+//! The callback is registered when the value is read for the first time.
+//!
+//! The name of the value is used as its key, so names have to be unique across all modules. Using
+//! the same name twice panics in debug builds when the second value is read, release builds log a
+//! warning and both share the value registered first.
+//!
+//! Doc comments are shown as a tooltip on the name of the value:
+//! ```rust
+//! /// Gravitational acceleration in m/s²
+//! #[const_tweaker::tweak(min = 0.0, max = 20.0)]
+//! const GRAVITY: f64 = 9.8;
+//! ```
+//!
+//! Values can be put in a group, every group is shown in a separate collapsible section. Values
+//! without a group are grouped by the name of the file they are declared in, so the values in
+//! `src/physics.rs` are shown in the "physics" section:
+//! ```rust
+//! #[const_tweaker::tweak(group = "Physics")]
+//! const FRICTION: f64 = 0.5;
+//! ```
+//!
+//! The key is shown as the name of the value, a label can be shown instead:
+//! ```rust
+//! #[const_tweaker::tweak(label = "Gravity (m/s²)", group = "Physics")]
+//! static GRAVITY: f64 = 9.8;
+//! ```
+//!
+//! Internal values can be left out of the web interface while they can still be changed with the
+//! REST API & from code:
+//! ```rust
+//! #[const_tweaker::tweak(hidden)]
+//! const INTERNAL_SEED: i64 = 42;
+//! ```
+//!
+//! Read-only values are shown in the web interface but can only be changed from code, which is
+//! useful for monitoring:
+//! ```rust
+//! #[const_tweaker::tweak(readonly)]
+//! const FRAME_TIME_MS: f64 = 0.0;
+//!
+//! // The value is registered the first time it's read
+//! assert_eq!(*FRAME_TIME_MS, 0.0);
+//!
+//! // The web interface refuses changes with a 403, but the program can still change it
+//! assert!(const_tweaker::set_f64("FRAME_TIME_MS", 16.6));
+//! assert_eq!(*FRAME_TIME_MS, 16.6);
+//! ```
+//!
+//! Every value can be changed from code with `set`, with `monitor` as a shorthand for read-only
+//! values the web interface becomes a panel showing the state of the program:
+//! ```rust
+//! #[const_tweaker::monitor]
+//! const ENEMY_COUNT: u32 = 0;
+//!
+//! // In the game loop
+//! ENEMY_COUNT.set(12);
+//! assert_eq!(*ENEMY_COUNT, 12);
+//! ```
+//!
+//! Other types can be tweaked by implementing the [`Tweakable`](trait.Tweakable.html) trait.
+//!
+//! Structs of which all fields are supported types can derive it, every field gets it's own input:
+//! ```rust
+//! use const_tweaker::Tweakable;
+//!
+//! #[derive(Debug, Clone, Tweakable)]
+//! struct Vec2 {
+//!     x: f64,
+//!     y: f64,
+//! }
+//!
+//! // Spawns the number inputs `OFFSET::x` & `OFFSET::y`
+//! #[const_tweaker::tweak]
+//! const OFFSET: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+//!
+//! let offset: Vec2 = OFFSET.get();
+//! ```
+//!
+//! Enums of which all variants are without fields can derive it too, they spawn a dropdown:
+//! ```rust
+//! use const_tweaker::Tweakable;
+//!
+//! #[derive(Debug, Clone, PartialEq, Tweakable)]
+//! enum Quality {
+//!     Low,
+//!     Medium,
+//!     High,
+//! }
+//!
+//! #[const_tweaker::tweak]
+//! const QUALITY: Quality = Quality::Medium;
+//!
+//! assert_eq!(QUALITY.get(), Quality::Medium);
+//! ```
+//!
+//! The `min`, `max`, `step` & `max_length` options only apply to the built-in widgets, so they
+//! can't be used with custom types:
+//! ```compile_fail
+//! #[const_tweaker::tweak(min = 0.0, max = 1.0)]
+//! const SPAWN_POINT: (f64, f64) = (0.0, 0.0);
+//! ```
+//!
+//! ## Health checks
+//!
+//! `GET /health` always responds with `{"status":"ok"}`, `GET /ready` responds with
+//! `{"ready":true}` while the web server accepts connections and with a `503` while it shuts down.
+//! Both don't require the authentication token. When running in a container the web server has
+//! to listen on an address that's reachable from outside, for example with
+//! `TweakerConfig::builder().host(...)`, and the prober has to be in the IP allowlist. In
+//! Kubernetes they can be used as probes like this:
+//! ```yaml
+//! livenessProbe:
+//!   httpGet:
+//!     path: /health
+//!     port: 9938
+//! readinessProbe:
+//!   httpGet:
+//!     path: /ready
+//!     port: 9938
+//! ```
+//!
+//! ## Features
+//!
+//! The web service is behind the `web` feature, which is enabled by default. Without it the
+//! values can still be changed programmatically, for example with `set_f64`, on targets without
+//! networking:
+//! ```toml
+//! [dependencies]
+//! const-tweaker = { version = "0.2", default-features = false }
+//! ```
+//!
+//! The `testing` feature adds the [`testing`](testing/index.html) module with helpers to change
+//! values in tests.
+//!
+//! The `compression` feature gzip compresses the page for browsers that support it, which is
+//! mostly the embedded stylesheet.
+
+use dashmap::{mapref::entry::Entry, DashMap};
+use horrorshow::owned_html;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    any::Any,
+    collections::{BTreeSet, HashMap, VecDeque},
+    convert::TryFrom,
+    error::Error,
+    fmt::Display,
+    fmt::{self, Formatter},
+    fs::{self, File, OpenOptions},
+    io::{self, LineWriter, Write},
+    net::IpAddr,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc, Mutex, RwLock,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
 
-use anyhow::Result;
-use async_std::task;
-use dashmap::DashMap;
-use horrorshow::{html, owned_html, Raw, Render};
-use serde::Deserialize;
-use std::thread;
-use tide::{Request, Response};
+pub use const_tweaker_attribute::{monitor, tweak};
+pub use const_tweaker_derive::Tweakable;
+#[doc(hidden)]
+pub use serde_json;
+#[cfg(feature = "web")]
+pub use web::{
+    run, run_async, run_async_with_config, run_on_random_port, run_with_config,
+    run_with_port_fallback, IpNet, IpNetParseError, TweakerConfig, TweakerConfigBuilder,
+    TweakerHandle,
+};
 
-pub use const_tweaker_attribute::tweak;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "testing")]
+#[doc(hidden)]
+pub use testing::mocked_value;
+#[cfg(feature = "web")]
+mod web;
 
 /// Type representing the const field with metadata.
 #[doc(hidden)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum Field {
     F64 {
         value: f64,
@@ -72,9 +421,105 @@ pub enum Field {
         /// Step increase of slider.
         step: f64,
     },
+    F32 {
+        value: f32,
+        /// Minimum value of slider.
+        min: f32,
+        /// Maximum value of slider.
+        max: f32,
+        /// Step increase of slider.
+        step: f32,
+    },
+    I32 {
+        value: i32,
+        /// Minimum value of number input.
+        min: i32,
+        /// Maximum value of number input.
+        max: i32,
+        /// Step increase of number input.
+        step: i32,
+    },
+    U32 {
+        value: u32,
+        /// Minimum value of number input.
+        min: u32,
+        /// Maximum value of number input.
+        max: u32,
+        /// Step increase of number input.
+        step: u32,
+    },
+    Usize {
+        value: usize,
+        /// Minimum value of number input.
+        min: usize,
+        /// Maximum value of number input.
+        max: usize,
+        /// Step increase of number input.
+        step: usize,
+    },
+    U8 {
+        value: u8,
+        /// Minimum value of slider.
+        min: u8,
+        /// Maximum value of slider.
+        max: u8,
+        /// Step increase of slider.
+        step: u8,
+    },
+    I64 {
+        value: i64,
+    },
+    U128 {
+        value: u128,
+    },
+    I128 {
+        value: i128,
+    },
+    U16 {
+        value: u16,
+        /// Minimum value of number input.
+        min: u16,
+        /// Maximum value of number input.
+        max: u16,
+        /// Step increase of number input.
+        step: u16,
+    },
+    I8 {
+        value: i8,
+        /// Minimum value of slider.
+        min: i8,
+        /// Maximum value of slider.
+        max: i8,
+        /// Step increase of slider.
+        step: i8,
+    },
+    I16 {
+        value: i16,
+        /// Minimum value of slider.
+        min: i16,
+        /// Maximum value of slider.
+        max: i16,
+        /// Step increase of slider.
+        step: i16,
+    },
+    String {
+        value: String,
+        /// Maximum length of the string in bytes.
+        max_length: usize,
+    },
     Bool {
         value: bool,
     },
+    Custom {
+        /// The value converted with `Tweakable::to_json`.
+        value: serde_json::Value,
+        /// The name of the type, see `Tweakable::type_name`.
+        type_name: &'static str,
+        /// Check whether JSON can be converted to the type.
+        check: fn(&serde_json::Value) -> Result<(), String>,
+        /// Render the HTML widget for the key & value.
+        render: fn(&str, &serde_json::Value) -> String,
+    },
 }
 
 impl Field {
@@ -89,10 +534,23 @@ impl Field {
         }
     }
 
-    /// Set a bool value when the field matches the proper variant.
-    pub fn set_bool(&mut self, new_value: bool) -> &Self {
+    /// Add to a f64 value when the field matches the proper variant, clamped to the bounds.
+    pub fn add_f64(&mut self, delta: f64) {
         match self {
-            Field::Bool { ref mut value, .. } => {
+            Field::F64 {
+                ref mut value,
+                min,
+                max,
+                ..
+            } => *value = (*value + delta).max(*min).min(*max),
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set a f32 value when the field matches the proper variant.
+    pub fn set_f32(&mut self, new_value: f32) -> &Self {
+        match self {
+            Field::F32 { ref mut value, .. } => {
                 *value = new_value;
                 self
             }
@@ -100,140 +558,3108 @@ impl Field {
         }
     }
 
-    /// Create a HTML widget from this field with it's metadata.
-    pub fn to_html_widget(&self, key: &str) -> String {
+    /// Set a i32 value when the field matches the proper variant.
+    pub fn set_i32(&mut self, new_value: i32) -> &Self {
         match self {
-            Field::F64 {
-                value,
-                min,
-                max,
-                step,
-            } => {
-                (owned_html! {
-                    div (class="column") {
-                        input (type="range",
-                            id=key,
-                            min=min,
-                            max=max,
-                            step=step,
-                            defaultValue=value,
-                            style="width: 100%",
-                            // The value is a string, convert it to a number so it can be properly
-                            // deserialized by serde
-                            oninput=send(key, "Number(this.value)", "f64"))
-                        { }
-                    }
-                    div (class="column is-narrow") {
-                        span (id=format!("{}_label", key), class="is-small")
-                        { : value }
-                    }
-                })
-                .to_string()
+            Field::I32 { ref mut value, .. } => {
+                *value = new_value;
+                self
             }
-            Field::Bool { value } => (owned_html! {
-                div (class="column") {
-                    input (type="checkbox",
-                        id=key,
-                        value=value.to_string(),
-                        onclick=send(key, "this.checked", "bool"))
-                    { }
-                }
-                div (class="column is-narrow") {
-                    span (id=format!("{}_label", key))
-                    { : value.to_string() }
-                }
-            })
-            .to_string(),
+            _ => panic!("Unexpected type, please report an issue"),
         }
     }
-}
-
-/// A struct used for deserializing POST request JSON data.
-#[derive(Debug, Deserialize)]
-struct PostData<T> {
-    key: String,
-    value: T,
-}
-
-lazy_static::lazy_static! {
-    /// The list of fields with their data.
-    #[doc(hidden)]
-    pub static ref DATA: DashMap<&'static str, Field> = DashMap::new();
-}
-
-/// Launch the `const` tweaker web service.
-///
-/// This will launch a web server at `http://127.0.01:9938`.
-pub fn run() -> Result<()> {
-    // Run a blocking web server in a new thread
-    thread::spawn(|| {
-        task::block_on(async {
-            let mut app = tide::new();
-            app.at("/").get(main_site);
-            app.at("/set/f64").post(handle_set_f64);
-            app.at("/set/bool").post(handle_set_bool);
-            app.listen("127.0.0.1:9938").await
-        })
-        .expect("Running web server failed");
-    });
-
-    Ok(())
-}
 
-/// Build the actual site.
-async fn main_site(_: Request<()>) -> Response {
-    let body = html! {
-        style { : include_str!("bulma.css") }
-        style { : "* { font-family: sans-serif}" }
-        div (class="container") {
-            h1 (class="title") { : "Const Tweaker Web Interface" }
-            p { : widgets() }
-            div (class="notification is-danger") {
-                span(id="status") { }
+    /// Set a u32 value when the field matches the proper variant.
+    pub fn set_u32(&mut self, new_value: u32) -> &Self {
+        match self {
+            Field::U32 { ref mut value, .. } => {
+                *value = new_value;
+                self
             }
+            _ => panic!("Unexpected type, please report an issue"),
         }
-        script { : Raw(include_str!("send.js")) }
-    };
+    }
 
-    Response::new(200)
-        .body_string(format!("{}", body))
-        .set_header("content-type", "text/html;charset=utf-8")
-}
+    /// Set a usize value when the field matches the proper variant.
+    pub fn set_usize(&mut self, new_value: usize) -> &Self {
+        match self {
+            Field::Usize { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
 
-/// Render all widgets.
-fn widgets() -> impl Render {
-    owned_html! {
-        @for ref_multi in DATA.iter() {
-            div (class="columns box") {
-                div (class="column is-narrow") {
-                    span (class="tag") { : ref_multi.key() }
-                }
-                : Raw(ref_multi.value().to_html_widget(ref_multi.key()))
+    /// Set a u8 value when the field matches the proper variant.
+    pub fn set_u8(&mut self, new_value: u8) -> &Self {
+        match self {
+            Field::U8 { ref mut value, .. } => {
+                *value = new_value;
+                self
             }
+            _ => panic!("Unexpected type, please report an issue"),
         }
     }
-}
 
-/// The javascript call to send the updated data.
-fn send(key: &str, look_for: &str, data_type: &str) -> String {
-    format!("send('{}', {}, '{}')", key, look_for, data_type)
-}
+    /// Set a i64 value when the field matches the proper variant.
+    pub fn set_i64(&mut self, new_value: i64) -> &Self {
+        match self {
+            Field::I64 { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
 
-// Handle setting of values
-async fn handle_set_f64(mut request: Request<()>) -> Response {
-    let post_data: PostData<f64> = request.body_json().await.expect("Could not decode JSON");
-    DATA.get_mut(&*post_data.key)
-        .expect("Could not get item from map")
-        .set_f64(post_data.value);
+    /// Set a u16 value when the field matches the proper variant.
+    pub fn set_u16(&mut self, new_value: u16) -> &Self {
+        match self {
+            Field::U16 { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set a i8 value when the field matches the proper variant.
+    pub fn set_i8(&mut self, new_value: i8) -> &Self {
+        match self {
+            Field::I8 { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set a i16 value when the field matches the proper variant.
+    pub fn set_i16(&mut self, new_value: i16) -> &Self {
+        match self {
+            Field::I16 { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set a u128 value when the field matches the proper variant.
+    pub fn set_u128(&mut self, new_value: u128) -> &Self {
+        match self {
+            Field::U128 { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set a i128 value when the field matches the proper variant.
+    pub fn set_i128(&mut self, new_value: i128) -> &Self {
+        match self {
+            Field::I128 { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set a string value when the field matches the proper variant.
+    pub fn set_string(&mut self, new_value: String) -> &Self {
+        match self {
+            Field::String { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set a bool value when the field matches the proper variant.
+    pub fn set_bool(&mut self, new_value: bool) -> &Self {
+        match self {
+            Field::Bool { ref mut value, .. } => {
+                *value = new_value;
+                self
+            }
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Flip a bool value when the field matches the proper variant.
+    pub fn toggle_bool(&mut self) {
+        match self {
+            Field::Bool { ref mut value } => *value = !*value,
+            _ => panic!("Unexpected type, please report an issue"),
+        }
+    }
+
+    /// Set the value from JSON, large integers have to be passed as strings.
+    pub fn set_json(&mut self, json: &serde_json::Value) -> Result<(), String> {
+        match self {
+            Field::F64 { value, .. } => *value = from_json(json)?,
+            Field::F32 { value, .. } => *value = from_json(json)?,
+            Field::I32 { value, .. } => *value = from_json(json)?,
+            Field::U32 { value, .. } => *value = from_json(json)?,
+            Field::Usize { value, .. } => *value = from_json(json)?,
+            Field::U8 { value, .. } => *value = from_json(json)?,
+            Field::U16 { value, .. } => *value = from_json(json)?,
+            Field::I8 { value, .. } => *value = from_json(json)?,
+            Field::I16 { value, .. } => *value = from_json(json)?,
+            Field::I64 { value } => *value = from_json_string(json)?,
+            Field::U128 { value } => *value = from_json_string(json)?,
+            Field::I128 { value } => *value = from_json_string(json)?,
+            Field::String { value, .. } => *value = from_json(json)?,
+            Field::Bool { value } => *value = from_json(json)?,
+            Field::Custom { value, check, .. } => {
+                check(json)?;
+                *value = json.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the value from text, like the value of an environment variable.
+    ///
+    /// Strings & the types that are sent as JSON strings are used as is, other types are parsed
+    /// as JSON.
+    pub fn set_str(&mut self, text: &str) -> Result<(), String> {
+        let json = match self {
+            Field::I64 { .. } | Field::U128 { .. } | Field::I128 { .. } | Field::String { .. } => {
+                json!(text)
+            }
+            _ => serde_json::from_str(text).map_err(|err| err.to_string())?,
+        };
+
+        self.set_json(&json)
+    }
+
+    /// The minimum & maximum as JSON for the types that have them.
+    pub fn bounds_to_json(&self) -> Option<(serde_json::Value, serde_json::Value)> {
+        match self {
+            Field::F64 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::F32 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::I32 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::U32 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::Usize { min, max, .. } => Some((json!(min), json!(max))),
+            Field::U8 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::U16 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::I8 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::I16 { min, max, .. } => Some((json!(min), json!(max))),
+            Field::I64 { .. }
+            | Field::U128 { .. }
+            | Field::I128 { .. }
+            | Field::String { .. }
+            | Field::Bool { .. }
+            | Field::Custom { .. } => None,
+        }
+    }
+
+    /// The error message when the value is outside of the bounds of the widget.
+    pub fn range_error(&self) -> Option<String> {
+        match self {
+            Field::F64 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::F32 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::I32 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::U32 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::Usize {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::U8 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::U16 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::I8 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::I16 {
+                value, min, max, ..
+            } => check_range(value, min, max),
+            Field::String { value, max_length } if value.len() > *max_length => Some(format!(
+                "String is {} bytes, the maximum length is {} bytes",
+                value.len(),
+                max_length
+            )),
+            Field::String { .. }
+            | Field::I64 { .. }
+            | Field::U128 { .. }
+            | Field::I128 { .. }
+            | Field::Bool { .. }
+            | Field::Custom { .. } => None,
+        }
+    }
+
+    /// The name of the type of the value, as used in the URL when setting it.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Field::F64 { .. } => "f64",
+            Field::F32 { .. } => "f32",
+            Field::I32 { .. } => "i32",
+            Field::U32 { .. } => "u32",
+            Field::Usize { .. } => "usize",
+            Field::U8 { .. } => "u8",
+            Field::U16 { .. } => "u16",
+            Field::I8 { .. } => "i8",
+            Field::I16 { .. } => "i16",
+            Field::I64 { .. } => "i64",
+            Field::U128 { .. } => "u128",
+            Field::I128 { .. } => "i128",
+            Field::String { .. } => "string",
+            Field::Bool { .. } => "bool",
+            Field::Custom { type_name, .. } => type_name,
+        }
+    }
+
+    /// The value as JSON, large integers are converted to strings so no precision is lost.
+    pub fn value_to_json(&self) -> serde_json::Value {
+        match self {
+            Field::F64 { value, .. } => json!(value),
+            Field::F32 { value, .. } => json!(value),
+            Field::I32 { value, .. } => json!(value),
+            Field::U32 { value, .. } => json!(value),
+            Field::Usize { value, .. } => json!(value),
+            Field::U8 { value, .. } => json!(value),
+            Field::U16 { value, .. } => json!(value),
+            Field::I8 { value, .. } => json!(value),
+            Field::I16 { value, .. } => json!(value),
+            Field::I64 { value } => json!(value.to_string()),
+            Field::U128 { value } => json!(value.to_string()),
+            Field::I128 { value } => json!(value.to_string()),
+            Field::String { value, .. } => json!(value),
+            Field::Bool { value } => json!(value),
+            Field::Custom { value, .. } => value.clone(),
+        }
+    }
+
+    /// Create a HTML widget from this field with a slider on a logarithmic scale for floats.
+    pub fn to_log_html_widget(&self, key: &str) -> String {
+        match *self {
+            Field::F64 {
+                value,
+                min,
+                max,
+                step,
+            } => float_widget(key, value, min, max, step, true, "f64"),
+            Field::F32 {
+                value,
+                min,
+                max,
+                step,
+            } => float_widget(key, value, min, max, step, true, "f32"),
+            _ => self.to_html_widget(key),
+        }
+    }
+
+    /// Create a HTML widget from this field with it's metadata.
+    pub fn to_html_widget(&self, key: &str) -> String {
+        match self {
+            Field::F64 {
+                value,
+                min,
+                max,
+                step,
+            } => float_widget(key, *value, *min, *max, *step, false, "f64"),
+            Field::F32 {
+                value,
+                min,
+                max,
+                step,
+            } => float_widget(key, *value, *min, *max, *step, false, "f32"),
+            Field::I32 {
+                value,
+                min,
+                max,
+                step,
+            } => number_widget(key, value, min, max, step, "i32"),
+            Field::U32 {
+                value,
+                min,
+                max,
+                step,
+            } => number_widget(key, value, min, max, step, "u32"),
+            Field::Usize {
+                value,
+                min,
+                max,
+                step,
+            } => number_widget(key, value, min, max, step, "usize"),
+            Field::U8 {
+                value,
+                min,
+                max,
+                step,
+            } => {
+                // Show the hexadecimal value as well so it's easy to compare with hex constants
+                let label = format!("{} (0x{:02X})", value, value);
+                slider_widget(key, value, min, max, step, label, "u8")
+            }
+            Field::U16 {
+                value,
+                min,
+                max,
+                step,
+            } => number_widget(key, value, min, max, step, "u16"),
+            Field::I8 {
+                value,
+                min,
+                max,
+                step,
+            } => slider_widget(key, value, min, max, step, value.to_string(), "i8"),
+            Field::I16 {
+                value,
+                min,
+                max,
+                step,
+            } => slider_widget(key, value, min, max, step, value.to_string(), "i16"),
+            Field::I64 { value } => text_widget(key, value, "-?[0-9]+", "i64"),
+            Field::U128 { value } => text_widget(key, value, "[0-9]+", "u128"),
+            Field::I128 { value } => text_widget(key, value, "-?[0-9]+", "i128"),
+            Field::String { value, max_length } => (owned_html! {
+                div (class="column") {
+                    input (type="text",
+                        id=key,
+                        class="input is-small",
+                        maxlength=max_length,
+                        value=value,
+                        oninput=send(key, "this.value", "string"))
+                    { }
+                }
+            })
+            .to_string(),
+            Field::Bool { value } => (owned_html! {
+                div (class="column") {
+                    input (type="checkbox",
+                        id=key,
+                        value=value.to_string(),
+                        onclick=send(key, "this.checked", "bool"))
+                    { }
+                }
+                div (class="column is-narrow") {
+                    span (id=format!("{}_label", key))
+                    { : value.to_string() }
+                }
+            })
+            .to_string(),
+            Field::Custom { value, render, .. } => render(key, value),
+        }
+    }
+}
+
+/// A custom type that can be tweaked.
+///
+/// The value is stored as JSON and converted back to the type every time it's changed. The HTML
+/// widget can change the value by calling the javascript function `send_json(key, value)`.
+///
+/// ```rust
+/// use const_tweaker::{Tweakable, TweakerError};
+/// use serde_json::json;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct Color {
+///     r: f32,
+///     g: f32,
+///     b: f32,
+/// }
+///
+/// impl Tweakable for Color {
+///     fn type_name() -> &'static str {
+///         "Color"
+///     }
+///
+///     fn to_json(&self) -> serde_json::Value {
+///         json!([self.r, self.g, self.b])
+///     }
+///
+///     fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+///         let [r, g, b]: [f32; 3] = serde_json::from_value(json)
+///             .map_err(|err| TweakerError::InvalidJson(err.to_string()))?;
+///
+///         Ok(Color { r, g, b })
+///     }
+///
+///     fn render_html(key: &str, value: &Self) -> String {
+///         // A color picker sending the color as an array of three floats
+///         format!(
+///             "<input type=\"color\" value=\"#{:02x}{:02x}{:02x}\" oninput=\"send_json('{}', \
+///              [1, 3, 5].map(i => parseInt(this.value.substr(i, 2), 16) / 255))\">",
+///             (value.r * 255.0) as u8,
+///             (value.g * 255.0) as u8,
+///             (value.b * 255.0) as u8,
+///             key
+///         )
+///     }
+/// }
+///
+/// #[const_tweaker::tweak]
+/// const BACKGROUND: Color = Color { r: 0.0, g: 0.0, b: 0.0 };
+///
+/// // Custom types are cloned when read
+/// assert_eq!(BACKGROUND.get(), Color { r: 0.0, g: 0.0, b: 0.0 });
+/// ```
+pub trait Tweakable: Send + Sync + Clone + 'static {
+    /// The name of the type, values with a different type can't be set.
+    fn type_name() -> &'static str;
+
+    /// Convert the value to JSON.
+    fn to_json(&self) -> serde_json::Value;
+
+    /// Convert JSON back to the value, returns an error when it's invalid.
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError>;
+
+    /// Render the HTML widget to change the value.
+    fn render_html(key: &str, value: &Self) -> String;
+}
+
+/// Check whether JSON can be converted to the custom type.
+fn check_tweakable<T: Tweakable>(json: &serde_json::Value) -> Result<(), String> {
+    T::from_json(json.clone())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+/// Render the HTML widget of the custom type.
+fn render_tweakable<T: Tweakable>(key: &str, json: &serde_json::Value) -> String {
+    match T::from_json(json.clone()) {
+        Ok(value) => T::render_html(key, &value),
+        Err(err) => format!("Invalid value: {}", err),
+    }
+}
+
+/// Register a value of a custom type.
+///
+/// This is called by the `tweak` attribute for types that are not supported by default. When a
+/// value with the key already exists it's kept, in debug builds this panics because it means the
+/// same name is used twice.
+pub fn register_tweakable<T: Tweakable>(key: &'static str, default: T) {
+    if DATA.contains_key(key) {
+        duplicate_key(key);
+        return;
+    }
+
+    let field = Field::Custom {
+        value: default.to_json(),
+        type_name: T::type_name(),
+        check: check_tweakable::<T>,
+        render: render_tweakable::<T>,
+    };
+    CUSTOM_VALUES.insert(key, Box::new(default));
+    DATA.insert(key, TweakEntry::new(field));
+
+    // Keep the converted value up to date
+    CALLBACKS
+        .entry(key)
+        .or_default()
+        .push(Arc::new(move |field| {
+            if let Field::Custom { value, .. } = field {
+                match T::from_json(value.clone()) {
+                    Ok(value) => {
+                        CUSTOM_VALUES.insert(key, Box::new(value));
+                    }
+                    Err(err) => log::warn!("Could not convert {}: {}", key, err),
+                }
+            }
+        }));
+}
+
+/// A primitive type that can be used as a field of a struct deriving `Tweakable`.
+#[doc(hidden)]
+pub trait TweakableField: Sized {
+    /// Convert the value to JSON, large integers are converted to strings.
+    fn to_json(&self) -> serde_json::Value;
+
+    /// Convert JSON back to the value.
+    fn from_json(json: &serde_json::Value) -> Result<Self, String>;
+
+    /// The type of the HTML input.
+    fn input_type() -> &'static str;
+}
+
+macro_rules! tweakable_field {
+    ($input_type:expr, $($ty:ty),*) => {
+        $(
+            impl TweakableField for $ty {
+                fn to_json(&self) -> serde_json::Value {
+                    json!(self)
+                }
+
+                fn from_json(json: &serde_json::Value) -> Result<Self, String> {
+                    from_json(json)
+                }
+
+                fn input_type() -> &'static str {
+                    $input_type
+                }
+            }
+        )*
+    };
+}
+
+tweakable_field!("number", f64, f32, i32, u32, usize, u8, u16, i8, i16);
+tweakable_field!("text", String);
+tweakable_field!("checkbox", bool);
+
+macro_rules! tweakable_string_field {
+    ($($ty:ty),*) => {
+        $(
+            impl TweakableField for $ty {
+                fn to_json(&self) -> serde_json::Value {
+                    json!(self.to_string())
+                }
+
+                fn from_json(json: &serde_json::Value) -> Result<Self, String> {
+                    from_json_string(json)
+                }
+
+                fn input_type() -> &'static str {
+                    "text"
+                }
+            }
+        )*
+    };
+}
+
+tweakable_string_field!(i64, u128, i128);
+
+/// Convert a field of a struct deriving `Tweakable` from the JSON object.
+#[doc(hidden)]
+pub fn struct_field<T: TweakableField>(
+    json: &serde_json::Value,
+    name: &str,
+) -> Result<T, TweakerError> {
+    let value = json
+        .get(name)
+        .ok_or_else(|| TweakerError::InvalidJson(format!("missing field {}", name)))?;
+
+    T::from_json(value).map_err(|err| TweakerError::InvalidJson(format!("field {}: {}", name, err)))
+}
+
+/// Render an input for every field of a struct deriving `Tweakable`.
+///
+/// The fields are the name, the HTML input type & the value as JSON.
+#[doc(hidden)]
+pub fn struct_widget(key: &str, fields: &[(&str, &str, serde_json::Value)]) -> String {
+    (owned_html! {
+        div (class="column") {
+            @for (name, input_type, value) in fields.iter() {
+                div (class="columns") {
+                    div (class="column is-narrow") {
+                        span (class="tag") { : format!("{}::{}", key, name) }
+                    }
+                    div (class="column") {
+                        input (type=input_type,
+                            id=format!("{}::{}", key, name),
+                            name=key,
+                            class=if *input_type == "checkbox" { "" } else { "input is-small" },
+                            step="any",
+                            value=match value {
+                                serde_json::Value::String(text) => text.clone(),
+                                value => value.to_string(),
+                            },
+                            checked?=value == &json!(true),
+                            oninput=format!("send_struct('{}')", key))
+                        { }
+                    }
+                }
+            }
+        }
+    })
+    .to_string()
+}
+
+/// 2D vectors, rendered as the inputs `x` & `y`.
+impl Tweakable for (f64, f64) {
+    fn type_name() -> &'static str {
+        "(f64, f64)"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({ "x": self.0, "y": self.1 })
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        Ok((struct_field(&json, "x")?, struct_field(&json, "y")?))
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        struct_widget(
+            key,
+            &[
+                ("x", "number", json!(value.0)),
+                ("y", "number", json!(value.1)),
+            ],
+        )
+    }
+}
+
+/// 3D vectors, rendered as the inputs `x`, `y` & `z`.
+impl Tweakable for (f64, f64, f64) {
+    fn type_name() -> &'static str {
+        "(f64, f64, f64)"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({ "x": self.0, "y": self.1, "z": self.2 })
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        Ok((
+            struct_field(&json, "x")?,
+            struct_field(&json, "y")?,
+            struct_field(&json, "z")?,
+        ))
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        struct_widget(
+            key,
+            &[
+                ("x", "number", json!(value.0)),
+                ("y", "number", json!(value.1)),
+                ("z", "number", json!(value.2)),
+            ],
+        )
+    }
+}
+
+/// RGBA colors with channels from `0.0` to `1.0`, rendered as a color picker & an alpha slider.
+impl Tweakable for [f32; 4] {
+    fn type_name() -> &'static str {
+        "[f32; 4]"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        let color: [f32; 4] = from_json(&json).map_err(TweakerError::InvalidJson)?;
+        match color.iter().find(|channel| !(0.0..=1.0).contains(*channel)) {
+            Some(channel) => Err(TweakerError::InvalidJson(format!(
+                "color channel {} is out of range, expected 0 to 1",
+                channel
+            ))),
+            None => Ok(color),
+        }
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        let [r, g, b, _] = value.map(|channel| (channel * 255.0).round() as u8);
+
+        (owned_html! {
+            div (class="column is-narrow") {
+                input (type="color",
+                    id=key,
+                    value=format!("#{:02x}{:02x}{:02x}", r, g, b),
+                    oninput=format!("send_color('{}')", key))
+                { }
+            }
+            div (class="column") {
+                input (type="range",
+                    id=format!("{}::a", key),
+                    class="slider is-fullwidth",
+                    title="Alpha",
+                    min=0,
+                    max=1,
+                    step=1.0 / 255.0,
+                    value=value[3],
+                    oninput=format!("send_color('{}')", key))
+                { }
+            }
+        })
+        .to_string()
+    }
+}
+
+/// Values that can be disabled, rendered as a checkbox enabling a number input.
+///
+/// ```rust
+/// // Use the automatically calculated field of view unless it's overridden
+/// #[const_tweaker::tweak]
+/// const OVERRIDE_FOV: Option<f64> = None;
+///
+/// assert_eq!(OVERRIDE_FOV.get(), None);
+/// ```
+impl Tweakable for Option<f64> {
+    fn type_name() -> &'static str {
+        "Option<f64>"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        from_json(&json).map_err(TweakerError::InvalidJson)
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        let number = value.map(|value| value.to_string()).unwrap_or_default();
+
+        (owned_html! {
+            div (class="column is-narrow") {
+                input (type="checkbox",
+                    id=format!("{}::enabled", key),
+                    title="Enabled",
+                    checked?=value.is_some(),
+                    onchange=format!("send_optional('{}', 'f64')", key))
+                { }
+            }
+            div (class="column") {
+                input (type="number",
+                    id=key,
+                    class="input is-small",
+                    step="any",
+                    value=&number,
+                    disabled?=value.is_none(),
+                    onchange=format!("send_optional('{}', 'f64')", key))
+                { }
+            }
+        })
+        .to_string()
+    }
+}
+
+/// Booleans that can be disabled, rendered as a checkbox enabling another checkbox.
+impl Tweakable for Option<bool> {
+    fn type_name() -> &'static str {
+        "Option<bool>"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        from_json(&json).map_err(TweakerError::InvalidJson)
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        (owned_html! {
+            div (class="column is-narrow") {
+                input (type="checkbox",
+                    id=format!("{}::enabled", key),
+                    title="Enabled",
+                    checked?=value.is_some(),
+                    onchange=format!("send_optional('{}', 'bool')", key))
+                { }
+            }
+            div (class="column") {
+                input (type="checkbox",
+                    id=key,
+                    checked?=value.unwrap_or_default(),
+                    disabled?=value.is_none(),
+                    onchange=format!("send_optional('{}', 'bool')", key))
+                { }
+            }
+        })
+        .to_string()
+    }
+}
+
+/// Durations in whole milliseconds, rendered as a number input.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// #[const_tweaker::tweak]
+/// const ANIMATION_DURATION: Duration = Duration::from_millis(300);
+///
+/// assert_eq!(ANIMATION_DURATION.get(), Duration::from_millis(300));
+/// ```
+impl Tweakable for Duration {
+    fn type_name() -> &'static str {
+        "Duration"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!(u64::try_from(self.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        // Negative & fractional milliseconds are rejected
+        let millis: u64 = from_json(&json).map_err(TweakerError::InvalidJson)?;
+
+        Ok(Duration::from_millis(millis))
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        let millis = Self::to_json(value);
+
+        (owned_html! {
+            div (class="column") {
+                div (class="field has-addons") {
+                    div (class="control") {
+                        input (type="number",
+                            id=key,
+                            class="input is-small",
+                            min=0,
+                            step=1,
+                            value=millis.to_string(),
+                            onchange=format!("send_json('{}', parseInt(this.value))", key))
+                        { }
+                    }
+                    div (class="control") {
+                        span (class="button is-small is-static") { : "ms" }
+                    }
+                }
+            }
+        })
+        .to_string()
+    }
+}
+
+/// Lists of which the length can change, rendered as a number input per element labeled by the
+/// index & buttons to add or remove an element.
+///
+/// ```rust
+/// // The coefficients of a polynomial
+/// #[const_tweaker::tweak]
+/// const COEFFICIENTS: Vec<f64> = vec![1.0, 0.0, 0.0];
+///
+/// assert_eq!(COEFFICIENTS.get(), vec![1.0, 0.0, 0.0]);
+/// ```
+impl Tweakable for Vec<f64> {
+    fn type_name() -> &'static str {
+        "Vec<f64>"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!(self)
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        from_json(&json).map_err(TweakerError::InvalidJson)
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        (owned_html! {
+            div (class="column") {
+                // The elements are recreated by the script when the length changes
+                div (id=format!("{}::elements", key)) {
+                    @for (index, element) in value.iter().enumerate() {
+                        div (class="field has-addons") {
+                            div (class="control") {
+                                span (class="button is-small is-static") { : index }
+                            }
+                            div (class="control") {
+                                input (type="number",
+                                    id=format!("{}::{}", key, index),
+                                    class="input is-small",
+                                    step="any",
+                                    value=element,
+                                    onchange=format!("send_vec('{}')", key))
+                                { }
+                            }
+                        }
+                    }
+                }
+                div (class="buttons") {
+                    button (class="button is-small", onclick=format!("resize_vec('{}', 1)", key)) {
+                        : "Add element"
+                    }
+                    button (class="button is-small", onclick=format!("resize_vec('{}', -1)", key)) {
+                        : "Remove last element"
+                    }
+                }
+            }
+        })
+        .to_string()
+    }
+}
+
+/// Render a dropdown with the variants of an enum deriving `Tweakable`.
+#[doc(hidden)]
+pub fn enum_widget(key: &str, variants: &[&str], selected: u64) -> String {
+    (owned_html! {
+        div (class="column") {
+            div (class="select is-small") {
+                select (id=key, onchange=send(key, "parseInt(this.value)", "enum")) {
+                    @for (index, variant) in variants.iter().enumerate() {
+                        option (value=index, selected?=index as u64 == selected) { : variant }
+                    }
+                }
+            }
+        }
+    })
+    .to_string()
+}
+
+/// Get a clone of the current value of a custom type.
+///
+/// Returns `None` when no value of the type is registered with the key.
+pub fn get_tweakable<T: Tweakable>(key: &str) -> Option<T> {
+    CUSTOM_VALUES.get(key)?.downcast_ref::<T>().cloned()
+}
+
+/// Values can only be mocked with the `testing` feature.
+#[cfg(not(feature = "testing"))]
+#[doc(hidden)]
+#[inline(always)]
+pub fn mocked_value<T>(_key: &str) -> Option<T> {
+    None
+}
+
+/// The `'static` references handed out by dereferencing a primitive value.
+///
+/// The value in the map can be changed at any moment, so a reference into it would dangle.
+/// Instead every new value is leaked once, so the amount of memory leaked is bounded by the
+/// amount of changes.
+#[doc(hidden)]
+pub struct StaticValue<T: 'static> {
+    last: Mutex<Option<&'static T>>,
+}
+
+impl<T: PartialEq> StaticValue<T> {
+    pub const fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Get a reference to a value equal to the current value.
+    #[allow(clippy::eq_op)]
+    pub fn get(&self, value: T) -> &'static T {
+        let mut last = self.last.lock().expect("Static value lock is poisoned");
+        match *last {
+            // NaN isn't equal to itself, it shouldn't be leaked on every dereference
+            Some(last) if *last == value || (*last != *last && value != value) => last,
+            _ => {
+                let leaked: &'static T = Box::leak(Box::new(value));
+                *last = Some(leaked);
+                leaked
+            }
+        }
+    }
+}
+
+impl<T: PartialEq> Default for StaticValue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A registered field with it's metadata.
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct TweakEntry {
+    /// The current value.
+    pub value: Field,
+    /// The value the field was registered with.
+    pub default: Field,
+    /// Optional description of the value.
+    pub description: Option<&'static str>,
+    /// The group the value is shown in, `None` groups it by the file.
+    pub group: Option<&'static str>,
+    /// The name shown in the web interface instead of the key.
+    pub label: Option<&'static str>,
+    /// The file the value is declared in.
+    pub file: Option<&'static str>,
+    /// Whether the value is refused to be changed.
+    pub locked: bool,
+    /// Whether the value is left out of the web interface, it can still be changed with the API.
+    pub hidden: bool,
+    /// Whether the value can only be changed from code, the web interface only shows it.
+    pub readonly: bool,
+    /// Whether the slider of a float uses a logarithmic scale.
+    pub log_scale: bool,
+    /// How often the value was changed since it was registered, resetting it also counts.
+    pub change_count: u64,
+}
+
+impl TweakEntry {
+    /// Register a field, the value is also used as the default.
+    pub fn new(field: Field) -> Self {
+        Self {
+            value: field.clone(),
+            default: field,
+            description: None,
+            group: None,
+            label: None,
+            file: None,
+            locked: false,
+            hidden: false,
+            readonly: false,
+            log_scale: false,
+            change_count: 0,
+        }
+    }
+}
+
+impl TweakEntry {
+    /// The group the value is shown in, values without a group are grouped by the file name.
+    pub fn group_name(&self) -> &'static str {
+        self.group
+            .or_else(|| self.file.and_then(file_group))
+            .unwrap_or(DEFAULT_GROUP)
+    }
+
+    /// Whether the value differs from the default.
+    pub fn is_modified(&self) -> bool {
+        self.value.value_to_json() != self.default.value_to_json()
+    }
+
+    /// The type, value, default & bounds as a JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = json!({
+            "type": self.value.type_name(),
+            "value": self.value.value_to_json(),
+            "default": self.default.value_to_json(),
+        });
+        if let Some((min, max)) = self.value.bounds_to_json() {
+            json["min"] = min;
+            json["max"] = max;
+        }
+
+        json
+    }
+}
+
+/// Deserialize a value from JSON.
+fn from_json<T: DeserializeOwned>(json: &serde_json::Value) -> Result<T, String> {
+    T::deserialize(json).map_err(|err| err.to_string())
+}
+
+/// Parse a value from a JSON string, used for integers that don't fit in a JSON number.
+fn from_json_string<T: FromStr>(json: &serde_json::Value) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    json.as_str()
+        .ok_or_else(|| "expected a string".to_string())?
+        .parse()
+        .map_err(|err: T::Err| err.to_string())
+}
+
+/// The error message when the value is not between the minimum & maximum.
+fn check_range<T: PartialOrd + Display>(value: &T, min: &T, max: &T) -> Option<String> {
+    if min <= value && value <= max {
+        None
+    } else {
+        Some(format!(
+            "Value {} is out of range, expected {} to {}",
+            value, min, max
+        ))
+    }
+}
+
+/// Clamp a value read with the `get` method generated by the macros to the bounds.
+#[doc(hidden)]
+pub fn clamp<T: PartialOrd>(value: T, min: T, max: T) -> T {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+/// Create a HTML slider widget with a label showing the current value.
+#[allow(clippy::too_many_arguments)]
+fn slider_widget<T: Display>(
+    key: &str,
+    value: T,
+    min: T,
+    max: T,
+    step: T,
+    label: String,
+    data_type: &str,
+) -> String {
+    (owned_html! {
+        div (class="column") {
+            input (type="range",
+                id=key,
+                min=min.to_string(),
+                max=max.to_string(),
+                step=step.to_string(),
+                defaultValue=value.to_string(),
+                style="width: 100%",
+                // The value is a string, convert it to a number so it can be properly
+                // deserialized by serde
+                oninput=send_debounced(key, "Number(this.value)", data_type),
+                onchange=send(key, "Number(this.value)", data_type))
+            { }
+        }
+        div (class="column is-narrow") {
+            span (id=format!("{}_label", key), class="is-small")
+            { : &label }
+        }
+    })
+    .to_string()
+}
+
+/// Create a HTML slider widget with a number input to enter a precise value.
+///
+/// The script keeps both in sync & rounds the number to the configured significant digits. On a
+/// logarithmic scale the position of the slider is the base 10 logarithm of the value.
+fn float_widget<T: Display + Into<f64> + Copy>(
+    key: &str,
+    value: T,
+    min: T,
+    max: T,
+    step: T,
+    log_scale: bool,
+    data_type: &str,
+) -> String {
+    let (slider_value, slider_min, slider_max, slider_step, look_for) = if log_scale {
+        (
+            value.into().log10().to_string(),
+            min.into().log10().to_string(),
+            max.into().log10().to_string(),
+            "any".to_string(),
+            "Math.pow(10, Number(this.value))",
+        )
+    } else {
+        (
+            value.to_string(),
+            min.to_string(),
+            max.to_string(),
+            step.to_string(),
+            "Number(this.value)",
+        )
+    };
+
+    (owned_html! {
+        div (class="column") {
+            input (type="range",
+                id=key,
+                min=&slider_min,
+                max=&slider_max,
+                step=&slider_step,
+                defaultValue=&slider_value,
+                data-scale=if log_scale { "log" } else { "linear" },
+                style="width: 100%",
+                oninput=send_debounced(key, look_for, data_type),
+                onchange=send(key, look_for, data_type))
+            { }
+        }
+        div (class="column is-narrow") {
+            // Any step is allowed so precise values & scientific notation can be entered
+            input (type="number",
+                id=format!("{}_number", key),
+                class="input is-small float-number",
+                min=min.to_string(),
+                max=max.to_string(),
+                step="any",
+                value=value.to_string(),
+                style="width: 9em",
+                onchange=format!("send_number('{}', this, '{}')", key, data_type))
+            { }
+        }
+    })
+    .to_string()
+}
+
+/// Create a HTML number input widget for integer values.
+fn number_widget<T: Display>(
+    key: &str,
+    value: T,
+    min: T,
+    max: T,
+    step: T,
+    data_type: &str,
+) -> String {
+    (owned_html! {
+        div (class="column") {
+            input (type="number",
+                id=key,
+                class="input is-small",
+                min=min.to_string(),
+                max=max.to_string(),
+                step=step.to_string(),
+                value=value.to_string(),
+                // Parse as an integer so a fractional value can never be sent
+                oninput=send(key, "parseInt(this.value, 10)", data_type))
+            { }
+        }
+    })
+    .to_string()
+}
+
+/// Create a HTML text input widget for integers that don't fit in a JSON number.
+///
+/// The value is sent as a string, but only when it matches the pattern.
+fn text_widget<T: Display>(key: &str, value: T, pattern: &str, data_type: &str) -> String {
+    (owned_html! {
+        div (class="column") {
+            input (type="text",
+                id=key,
+                class="input is-small",
+                pattern=pattern,
+                value=value.to_string(),
+                oninput=format!("if (this.checkValidity()) {}", send(key, "this.value", data_type)))
+            { }
+        }
+    })
+    .to_string()
+}
+
+lazy_static::lazy_static! {
+    /// The list of fields with their data.
+    #[doc(hidden)]
+    pub static ref DATA: DashMap<&'static str, TweakEntry> = DashMap::new();
+
+    /// The callbacks called with the new field when a value changes, in registration order.
+    #[doc(hidden)]
+    pub static ref CALLBACKS: DashMap<&'static str, Vec<Callback>> = DashMap::new();
+
+    /// The current values of the custom types, which are converted from the fields.
+    static ref CUSTOM_VALUES: DashMap<&'static str, Box<dyn Any + Send + Sync>> = DashMap::new();
+
+    /// The subscribers that are sent the new value when a value changes.
+    static ref WATCHERS: DashMap<&'static str, Vec<Watcher>> = DashMap::new();
+
+    /// The custom function called with the old & new value when a value changes.
+    static ref LOG_FN: RwLock<Option<LogFn>> = RwLock::new(None);
+
+    /// The most recent value changes, newest last.
+    static ref CHANGE_LOG: Mutex<VecDeque<ChangeLogEntry>> = Mutex::new(VecDeque::new());
+
+    /// The snapshots saved with `save_preset` by name.
+    static ref PRESETS: DashMap<String, Snapshot> = DashMap::new();
+
+    /// The files all value changes are written to by `record_changes_to_file`, by handle ID.
+    static ref RECORDERS: Mutex<Vec<(u64, LineWriter<File>)>> = Mutex::new(Vec::new());
+
+    /// The invariants added with `tweak_assert!` with their message.
+    static ref ASSERTIONS: DashMap<&'static str, Vec<(String, Assertion)>> = DashMap::new();
+}
+
+/// The maximum amount of entries kept in the change log.
+static CHANGE_LOG_SIZE: AtomicUsize = AtomicUsize::new(1000);
+
+/// The amount of value changes, exposed as a Prometheus counter.
+#[cfg(feature = "metrics")]
+static CHANGE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Function called with the new field when a value changes.
+#[doc(hidden)]
+pub type Callback = Arc<dyn Fn(&Field) + Send + Sync>;
+
+/// Function sending the new field to a subscriber, returns `false` when the receiver is dropped.
+type Watcher = Box<dyn Fn(&Field) -> bool + Send + Sync>;
+
+/// Function checking an invariant on the value, boxed again so the checks of all types can be
+/// stored in the same map.
+type Assertion = Box<dyn Any + Send + Sync>;
+
+/// Function called with the key, the old value & the new value when a value changes.
+type LogFn = Arc<dyn Fn(&str, serde_json::Value, serde_json::Value) + Send + Sync>;
+
+/// A value change recorded in the change log.
+///
+/// The values are in the same format as the `value` returned by `get_all`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeLogEntry {
+    /// When the value was changed.
+    pub timestamp: SystemTime,
+    /// The key of the changed value.
+    pub key: String,
+    /// The value before the change.
+    pub old_value: serde_json::Value,
+    /// The value after the change.
+    pub new_value: serde_json::Value,
+    /// The address of the client that changed the value from the web interface, `None` when it's
+    /// changed from code or over a Unix socket.
+    pub source_ip: Option<IpAddr>,
+}
+
+/// Error running the `const` tweaker web service, saving & loading values or converting them.
+#[derive(Debug)]
+pub enum TweakerError {
+    /// The web server couldn't listen on the configured address.
+    BindFailed(io::Error),
+    /// A web server is already running in this process.
+    AlreadyRunning,
+    /// The thread running the web server couldn't be started.
+    RuntimeError(String),
+    /// None of the ports in the range could be bound.
+    NoPortAvailable { start: u16, end: u16 },
+    /// The file with the values couldn't be read or written.
+    Io(io::Error),
+    /// The file with the values isn't valid JSON.
+    InvalidFile(serde_json::Error),
+    /// A value in the file can't be loaded.
+    InvalidValue { key: String, reason: String },
+    /// JSON can't be converted to a custom type or a snapshot.
+    InvalidJson(String),
+    /// The CSV isn't valid or is missing a column.
+    InvalidCsv(String),
+    /// The replay speed isn't a finite number larger than zero.
+    InvalidSpeed(f64),
+}
+
+impl Display for TweakerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TweakerError::BindFailed(err) => write!(f, "Could not bind web server: {}", err),
+            TweakerError::AlreadyRunning => write!(f, "The web server is already running"),
+            TweakerError::RuntimeError(err) => write!(f, "Could not run web server: {}", err),
+            TweakerError::NoPortAvailable { start, end } => {
+                write!(f, "No port available from {} to {}", start, end)
+            }
+            TweakerError::Io(err) => write!(f, "Could not access file: {}", err),
+            TweakerError::InvalidFile(err) => write!(f, "Invalid file: {}", err),
+            TweakerError::InvalidValue { key, reason } => {
+                write!(f, "Could not load {}: {}", key, reason)
+            }
+            TweakerError::InvalidJson(err) => write!(f, "Invalid JSON: {}", err),
+            TweakerError::InvalidCsv(err) => write!(f, "Invalid CSV: {}", err),
+            TweakerError::InvalidSpeed(speed) => write!(
+                f,
+                "Invalid replay speed {}, it must be larger than zero",
+                speed
+            ),
+        }
+    }
+}
+
+impl Error for TweakerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TweakerError::BindFailed(err) | TweakerError::Io(err) => Some(err),
+            TweakerError::InvalidFile(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Restore all values to the value they were registered with.
+///
+/// Locked values are reset as well.
+pub fn reset_to_defaults() {
+    let keys: Vec<&'static str> = DATA.iter().map(|entry| *entry.key()).collect();
+    keys.into_iter().for_each(|key| {
+        reset_value(key);
+    });
+}
+
+/// Restore a single value to the value it was registered with.
+///
+/// Returns `false` when no value with the key is registered.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// assert!(const_tweaker::reset_value("VALUE"));
+/// assert!(!const_tweaker::reset_value("UNKNOWN_VALUE"));
+/// ```
+pub fn reset_value(key: &str) -> bool {
+    let (old, field) = match DATA.get_mut(key) {
+        Some(mut entry) => {
+            let default = entry.default.clone();
+            (
+                std::mem::replace(&mut entry.value, default.clone()),
+                default,
+            )
+        }
+        None => return false,
+    };
+    notify_change(key, &old, &field);
+
+    true
+}
+
+/// Refuse all changes to a value, except for resetting it from code, until it's unlocked.
+///
+/// Changing or resetting a locked value from the web interface responds with a 423, resetting all
+/// values from the web interface skips it. Returns `false` when no value with the key is
+/// registered.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: bool = false;
+///
+/// // The value is registered the first time it's read
+/// assert!(!*VALUE);
+///
+/// assert!(const_tweaker::lock_value("VALUE"));
+/// assert!(!const_tweaker::set_bool("VALUE", true));
+///
+/// assert!(const_tweaker::unlock_value("VALUE"));
+/// assert!(const_tweaker::set_bool("VALUE", true));
+/// ```
+pub fn lock_value(key: &str) -> bool {
+    set_locked(key, true)
+}
+
+/// Allow changes to a value locked with `lock_value` again.
+///
+/// Returns `false` when no value with the key is registered.
+pub fn unlock_value(key: &str) -> bool {
+    set_locked(key, false)
+}
+
+/// Lock all registered values.
+pub fn lock_all() {
+    DATA.iter_mut().for_each(|mut entry| entry.locked = true);
+}
+
+/// Unlock all registered values.
+pub fn unlock_all() {
+    DATA.iter_mut().for_each(|mut entry| entry.locked = false);
+}
+
+fn set_locked(key: &str, locked: bool) -> bool {
+    match DATA.get_mut(key) {
+        Some(mut entry) => {
+            entry.locked = locked;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Change a `f64` value.
+///
+/// The callbacks & subscribers are notified of the new value. Returns `false` when no `f64` value
+/// with the key is registered, when the value is locked or when it's outside of the bounds.
+///
+/// ```rust
+/// #[const_tweaker::tweak(min = 0.0, max = 1.0)]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// assert!(const_tweaker::set_f64("VALUE", 0.5));
+/// assert_eq!(*VALUE, 0.5);
+///
+/// assert!(!const_tweaker::set_f64("VALUE", 2.0));
+/// assert_eq!(*VALUE, 0.5);
+/// ```
+pub fn set_f64(key: &str, value: f64) -> bool {
+    set_field(key, "f64", |field| {
+        field.set_f64(value);
+    })
+    .is_ok()
+}
+
+/// Register a value, this is called by the `tweak` attribute.
+///
+/// When a value with the key already exists it's kept, in debug builds this panics because it
+/// means the same name is used twice.
+#[doc(hidden)]
+pub fn register_value(key: &'static str, entry: TweakEntry) {
+    // The entry has to be released before panicking
+    let registered = match DATA.entry(key) {
+        Entry::Occupied(_) => false,
+        Entry::Vacant(vacant) => {
+            vacant.insert(entry);
+            true
+        }
+    };
+    if !registered {
+        duplicate_key(key);
+    }
+}
+
+/// Report that a value with the same key is registered twice.
+///
+/// The names of the values are used as the keys, so values with the same name in different
+/// modules would silently share a value.
+fn duplicate_key(key: &str) {
+    if cfg!(debug_assertions) {
+        panic!("Duplicate tweak key: {}", key);
+    } else {
+        log::warn!(
+            "Duplicate tweak key: {}, the value registered first is used",
+            key
+        );
+    }
+}
+
+/// Check an invariant every time a value is read.
+///
+/// A violated invariant panics in debug builds and logs a warning in release builds, with the
+/// message containing the key & the current value. This also catches values set from code, which
+/// the slider bounds don't.
+///
+/// The invariant is added when the macro is called, so call it once when starting the program.
+/// Calling it again with the same message replaces the invariant. When no message is passed the
+/// condition is used as the message.
+///
+/// ```rust,should_panic
+/// #[const_tweaker::tweak]
+/// const GRAVITY: f64 = 9.8;
+///
+/// const_tweaker::tweak_assert! { GRAVITY > 0.0, "Gravity must be positive" }
+///
+/// GRAVITY.set(-1.0);
+/// // Panics with "Tweak assertion failed for GRAVITY = -1.0: Gravity must be positive"
+/// let gravity = *GRAVITY;
+/// ```
+#[macro_export]
+macro_rules! tweak_assert {
+    ($name:ident $op:tt $rhs:expr $(,)?) => {
+        $crate::tweak_assert!($name $op $rhs, stringify!($name $op $rhs))
+    };
+    ($name:ident $op:tt $rhs:expr, $message:expr $(,)?) => {
+        $name.assert_with($message.to_string(), move |value| *value $op $rhs)
+    };
+}
+
+/// Add an invariant for a value with the `assert_with` method generated by the macros.
+#[doc(hidden)]
+pub fn register_assertion<T: 'static>(
+    key: &'static str,
+    message: String,
+    check: impl Fn(&T) -> bool + Send + Sync + 'static,
+) {
+    let check: Box<dyn Fn(&T) -> bool + Send + Sync> = Box::new(check);
+
+    let mut assertions = ASSERTIONS.entry(key).or_default();
+    match assertions
+        .iter_mut()
+        .find(|(existing, _)| *existing == message)
+    {
+        Some((_, existing)) => *existing = Box::new(check),
+        None => assertions.push((message, Box::new(check))),
+    }
+}
+
+/// Check the invariants of a value when it's read by the `get` method generated by the macros.
+#[doc(hidden)]
+pub fn check_assertions<T: 'static>(key: &str, value: &T, describe: impl FnOnce(&T) -> String) {
+    let failed = match ASSERTIONS.get(key) {
+        Some(assertions) => assertions
+            .iter()
+            .find(|(_, check)| {
+                check
+                    .downcast_ref::<Box<dyn Fn(&T) -> bool + Send + Sync>>()
+                    .is_some_and(|check| !check(value))
+            })
+            .map(|(message, _)| message.clone()),
+        None => None,
+    };
+
+    // The map is released before panicking
+    if let Some(message) = failed {
+        if cfg!(debug_assertions) {
+            panic!(
+                "Tweak assertion failed for {} = {}: {}",
+                key,
+                describe(value),
+                message
+            );
+        } else {
+            log::warn!(
+                "Tweak assertion failed for {} = {}: {}",
+                key,
+                describe(value),
+                message
+            );
+        }
+    }
+}
+
+/// Change a value with the `set` method generated by the macros.
+///
+/// Returns `false` when no value with the key is registered, when the value is locked or when
+/// it's outside of the bounds.
+#[doc(hidden)]
+pub fn set_with(key: &str, update: impl FnOnce(&mut Field)) -> bool {
+    let type_name = match DATA.get(key) {
+        Some(entry) => entry.value.type_name(),
+        None => return false,
+    };
+
+    set_field(key, type_name, update).is_ok()
+}
+
+/// Change a `bool` value.
+///
+/// The callbacks & subscribers are notified of the new value. Returns `false` when no `bool`
+/// value with the key is registered or when the value is locked.
+pub fn set_bool(key: &str, value: bool) -> bool {
+    set_field(key, "bool", |field| {
+        field.set_bool(value);
+    })
+    .is_ok()
+}
+
+/// Add a delta to a `f64` value, the result is clamped to the bounds of the value.
+///
+/// The value is changed atomically, so concurrent deltas are never lost. The callbacks &
+/// subscribers are notified of the new value, which is returned. Returns `None` when no `f64`
+/// value with the key is registered or when the value is locked.
+///
+/// ```rust
+/// #[const_tweaker::tweak(min = 0.0, max = 1.0)]
+/// const VALUE: f64 = 0.5;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.5);
+///
+/// assert_eq!(const_tweaker::apply_delta_f64("VALUE", 0.25), Some(0.75));
+/// assert_eq!(const_tweaker::apply_delta_f64("VALUE", 0.5), Some(1.0));
+/// ```
+pub fn apply_delta_f64(key: &str, delta: f64) -> Option<f64> {
+    match alter_field(key, "f64", |field| field.add_f64(delta)) {
+        Ok(Field::F64 { value, .. }) => Some(value),
+        _ => None,
+    }
+}
+
+/// Flip a `bool` value.
+///
+/// The value is changed atomically, so concurrent toggles are never lost. The callbacks &
+/// subscribers are notified of the new value, which is returned. Returns `None` when no `bool`
+/// value with the key is registered or when the value is locked.
+pub fn toggle_bool(key: &str) -> Option<bool> {
+    match alter_field(key, "bool", Field::toggle_bool) {
+        Ok(Field::Bool { value }) => Some(value),
+        _ => None,
+    }
+}
+
+/// Subscribe to changes of a `f64` value.
+///
+/// Every time the value is changed the new value is sent to the receiver. Returns `None` when no
+/// `f64` value with the key is registered.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// let receiver = const_tweaker::watch_f64("VALUE").unwrap();
+/// assert!(receiver.try_recv().is_err());
+/// ```
+pub fn watch_f64(key: &str) -> Option<Receiver<f64>> {
+    watch(key, |field| match field {
+        Field::F64 { value, .. } => Some(*value),
+        _ => None,
+    })
+}
+
+/// Subscribe to changes of a `bool` value.
+///
+/// Every time the value is changed the new value is sent to the receiver. Returns `None` when no
+/// `bool` value with the key is registered.
+pub fn watch_bool(key: &str) -> Option<Receiver<bool>> {
+    watch(key, |field| match field {
+        Field::Bool { value } => Some(*value),
+        _ => None,
+    })
+}
+
+/// How often a value was changed since it was registered, `0` when it's not registered.
+///
+/// Resetting the value to the default also counts as a change, the count is never reset.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// const_tweaker::set_f64("VALUE", 0.5);
+/// const_tweaker::reset_value("VALUE");
+/// assert_eq!(const_tweaker::change_count("VALUE"), 2);
+/// ```
+pub fn change_count(key: &str) -> u64 {
+    DATA.get(key).map_or(0, |entry| entry.change_count)
+}
+
+/// The default of a `f64` value, `None` when no `f64` value with the key is registered.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.5;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.5);
+///
+/// const_tweaker::set_f64("VALUE", 0.25);
+/// assert_eq!(const_tweaker::get_default_f64("VALUE"), Some(0.5));
+/// ```
+pub fn get_default_f64(key: &str) -> Option<f64> {
+    match DATA.get(key)?.default {
+        Field::F64 { value, .. } => Some(value),
+        _ => None,
+    }
+}
+
+/// The default of a `bool` value, `None` when no `bool` value with the key is registered.
+pub fn get_default_bool(key: &str) -> Option<bool> {
+    match DATA.get(key)?.default {
+        Field::Bool { value } => Some(value),
+        _ => None,
+    }
+}
+
+/// Get a copy of all registered `f64` values.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.5;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.5);
+///
+/// assert_eq!(const_tweaker::get_all_f64().get("VALUE"), Some(&0.5));
+/// ```
+pub fn get_all_f64() -> HashMap<String, f64> {
+    DATA.iter()
+        .filter_map(|entry| match entry.value {
+            Field::F64 { value, .. } => Some((entry.key().to_string(), value)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Get a copy of all registered `bool` values.
+pub fn get_all_bool() -> HashMap<String, bool> {
+    DATA.iter()
+        .filter_map(|entry| match entry.value {
+            Field::Bool { value } => Some((entry.key().to_string(), value)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Get a copy of all registered values of all types.
+///
+/// Every value is a JSON object containing the `type` and the `value`, `i64`, `u128` & `i128`
+/// values are converted to strings so no precision is lost.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: bool = true;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, true);
+///
+/// assert_eq!(
+///     const_tweaker::get_all()["VALUE"],
+///     serde_json::json!({ "type": "bool", "value": true })
+/// );
+/// ```
+pub fn get_all() -> HashMap<String, serde_json::Value> {
+    DATA.iter()
+        .map(|entry| {
+            (
+                entry.key().to_string(),
+                json!({
+                    "type": entry.value.type_name(),
+                    "value": entry.value.value_to_json(),
+                }),
+            )
+        })
+        .collect()
+}
+
+/// Description of a registered value with it's metadata.
+///
+/// The values are in the same format as the `value` returned by `get_all`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TweakInfo {
+    /// The key of the value.
+    pub key: &'static str,
+    /// The name of the type, like `f64`.
+    pub type_name: &'static str,
+    /// The description shown in the web interface.
+    pub description: Option<&'static str>,
+    /// The value the value was registered with.
+    pub default: serde_json::Value,
+    /// The current value.
+    pub current: serde_json::Value,
+    /// The minimum for the types that have bounds.
+    pub min: Option<serde_json::Value>,
+    /// The maximum for the types that have bounds.
+    pub max: Option<serde_json::Value>,
+    /// Whether the value can't be changed.
+    pub locked: bool,
+    /// Whether the value isn't shown in the web interface.
+    pub hidden: bool,
+    /// Whether the value can't be changed from the web interface.
+    pub readonly: bool,
+    /// How often the value was changed since it was registered.
+    pub change_count: u64,
+}
+
+/// Describe all registered values, sorted by key.
+///
+/// ```rust
+/// #[const_tweaker::tweak(min = 0.0, max = 10.0)]
+/// const VALUE: f64 = 5.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 5.0);
+///
+/// let info = &const_tweaker::enumerate_tweaks()[0];
+/// assert_eq!(info.key, "VALUE");
+/// assert_eq!(info.type_name, "f64");
+/// assert_eq!(info.max, Some(serde_json::json!(10.0)));
+/// ```
+pub fn enumerate_tweaks() -> Vec<TweakInfo> {
+    collect_tweaks(|_| true)
+}
+
+/// The keys of all registered values, sorted.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const SPEED: f64 = 1.0;
+///
+/// #[const_tweaker::tweak]
+/// const PAUSED: bool = false;
+///
+/// // The values are registered the first time they're read
+/// assert_eq!(*SPEED, 1.0);
+/// assert!(!*PAUSED);
+///
+/// assert_eq!(const_tweaker::get_registered_keys(), vec!["PAUSED", "SPEED"]);
+/// assert_eq!(const_tweaker::get_f64_keys(), vec!["SPEED"]);
+/// assert_eq!(const_tweaker::get_keys_by_type("bool"), vec!["PAUSED"]);
+/// ```
+pub fn get_registered_keys() -> Vec<&'static str> {
+    sorted_keys(|_| true)
+}
+
+/// The keys of all registered values with the type, like `"i32"` or the name of a custom type,
+/// sorted.
+pub fn get_keys_by_type(type_name: &str) -> Vec<&'static str> {
+    sorted_keys(|field| field.type_name() == type_name)
+}
+
+/// The keys of all registered `f64` values, sorted.
+pub fn get_f64_keys() -> Vec<&'static str> {
+    get_keys_by_type("f64")
+}
+
+/// The keys of all registered `bool` values, sorted.
+pub fn get_bool_keys() -> Vec<&'static str> {
+    get_keys_by_type("bool")
+}
+
+/// The amount of registered values.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const SPEED: f64 = 1.0;
+///
+/// #[const_tweaker::tweak]
+/// const PAUSED: bool = false;
+///
+/// // The values are registered the first time they're read
+/// assert_eq!(*SPEED, 1.0);
+/// assert!(!*PAUSED);
+///
+/// assert_eq!(const_tweaker::count(), 2);
+/// assert_eq!(const_tweaker::count_by_type().get("f64"), Some(&1));
+/// ```
+pub fn count() -> usize {
+    DATA.len()
+}
+
+/// The amount of registered values by the name of the type, like `"i32"` or the name of a custom
+/// type.
+pub fn count_by_type() -> HashMap<&'static str, usize> {
+    DATA.iter().fold(HashMap::new(), |mut counts, entry| {
+        *counts.entry(entry.value.type_name()).or_default() += 1;
+        counts
+    })
+}
+
+/// The keys of the values matching the filter, sorted.
+fn sorted_keys(filter: impl Fn(&Field) -> bool) -> Vec<&'static str> {
+    let mut keys: Vec<&'static str> = DATA
+        .iter()
+        .filter(|entry| filter(&entry.value))
+        .map(|entry| *entry.key())
+        .collect();
+    keys.sort_unstable();
+
+    keys
+}
+
+/// Print all registered values as a table, sorted by key.
+///
+/// This is useful to check which values are registered while developing, the columns are the
+/// key, the type, the default & current values and whether the value is modified.
+pub fn list() {
+    print!("{}", list_table());
+}
+
+/// All registered values as pretty-printed JSON, sorted by key.
+///
+/// It contains the same columns as `list`, the output is stable so it can be compared between
+/// runs.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: bool = false;
+///
+/// // The value is registered the first time it's read
+/// assert!(!*VALUE);
+///
+/// let list: serde_json::Value = serde_json::from_str(&const_tweaker::list_json()).unwrap();
+/// assert_eq!(
+///     list,
+///     serde_json::json!([{
+///         "key": "VALUE",
+///         "type": "bool",
+///         "default": false,
+///         "current": false,
+///         "modified": false,
+///     }])
+/// );
+/// ```
+pub fn list_json() -> String {
+    serde_json::to_string_pretty(&list_rows()).expect("Could not encode JSON")
+}
+
+/// The rows of `list` & `list_json`.
+fn list_rows() -> Vec<serde_json::Value> {
+    enumerate_tweaks()
+        .into_iter()
+        .map(|info| {
+            json!({
+                "key": info.key,
+                "type": info.type_name,
+                "modified": info.default != info.current,
+                "default": info.default,
+                "current": info.current,
+            })
+        })
+        .collect()
+}
+
+/// Format the registered values as a table with aligned columns.
+fn list_table() -> String {
+    let header = ["KEY", "TYPE", "DEFAULT", "CURRENT", "MODIFIED"];
+    let rows: Vec<[String; 5]> = enumerate_tweaks()
+        .into_iter()
+        .map(|info| {
+            [
+                info.key.to_string(),
+                info.type_name.to_string(),
+                info.default.to_string(),
+                info.current.to_string(),
+                (info.default != info.current).to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for row in rows.iter() {
+        for (width, column) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    let header = header.map(str::to_string);
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(column, width)| format!("{:width$}", column, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+
+    table
+}
+
+/// Whether the value registered with the key differs from its default.
+///
+/// Returns `false` when no value with the key is registered.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: bool = false;
+///
+/// // The value is registered the first time it's read
+/// assert!(!*VALUE);
+/// assert!(!const_tweaker::is_modified("VALUE"));
+///
+/// const_tweaker::set_bool("VALUE", true);
+/// assert!(const_tweaker::is_modified("VALUE"));
+/// ```
+pub fn is_modified(key: &str) -> bool {
+    DATA.get(key).is_some_and(|entry| entry.is_modified())
+}
+
+/// Information about all values that differ from their defaults, sorted by key.
+///
+/// This can be used to ask to save the changes before exiting.
+pub fn modified_tweaks() -> Vec<TweakInfo> {
+    collect_tweaks(TweakEntry::is_modified)
+}
+
+/// Information about the values matching the filter, sorted by key.
+fn collect_tweaks(filter: impl Fn(&TweakEntry) -> bool) -> Vec<TweakInfo> {
+    let mut tweaks: Vec<TweakInfo> = DATA
+        .iter()
+        .filter(|entry| filter(entry.value()))
+        .map(|entry| {
+            let (min, max) = match entry.value.bounds_to_json() {
+                Some((min, max)) => (Some(min), Some(max)),
+                None => (None, None),
+            };
+
+            TweakInfo {
+                key: entry.key(),
+                type_name: entry.value.type_name(),
+                description: entry.description,
+                default: entry.default.value_to_json(),
+                current: entry.value.value_to_json(),
+                min,
+                max,
+                locked: entry.locked,
+                hidden: entry.hidden,
+                readonly: entry.readonly,
+                change_count: entry.change_count,
+            }
+        })
+        .collect();
+    tweaks.sort_by_key(|info| info.key);
+
+    tweaks
+}
+
+/// The state of all registered values at a point in time.
+///
+/// The values are in the same format as the ones returned by `get_all`.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// let snapshot = const_tweaker::take_snapshot();
+/// const_tweaker::set_f64("VALUE", 0.5);
+/// assert_eq!(*VALUE, 0.5);
+///
+/// const_tweaker::restore_snapshot(&snapshot);
+/// assert_eq!(*VALUE, 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Snapshot {
+    /// The values by key, every value is a JSON object containing the `type` and the `value`.
+    pub values: HashMap<String, serde_json::Value>,
+}
+
+impl From<Snapshot> for serde_json::Value {
+    fn from(snapshot: Snapshot) -> Self {
+        serde_json::Value::Object(snapshot.values.into_iter().collect())
+    }
+}
+
+impl TryFrom<serde_json::Value> for Snapshot {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+/// Capture the current state of all registered values.
+pub fn take_snapshot() -> Snapshot {
+    Snapshot { values: get_all() }
+}
+
+/// Restore all values to the state captured in the snapshot.
+///
+/// The callbacks & subscribers are notified of every value that changed. Values that can't be
+/// restored, because they aren't registered, have a different type, are locked or are out of
+/// bounds, are skipped with a warning.
+pub fn restore_snapshot(snapshot: &Snapshot) {
+    let updates = snapshot
+        .values
+        .iter()
+        .filter_map(|(key, json)| match snapshot_update(key, json) {
+            Ok(field) => field.map(|field| (key.as_str(), field)),
+            Err(err) => {
+                log::warn!("Could not restore {}: {}", key, err);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    store_fields(updates);
+}
+
+/// Capture the current state of all registered values & store it under a name.
+///
+/// A preset with the same name is replaced. The stored snapshot is returned.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// const_tweaker::save_preset("flat");
+/// const_tweaker::set_f64("VALUE", 0.5);
+///
+/// assert_eq!(const_tweaker::load_preset("flat"), Some(()));
+/// assert_eq!(*VALUE, 0.0);
+/// assert_eq!(const_tweaker::presets(), vec!["flat"]);
+/// ```
+pub fn save_preset(name: &str) -> Snapshot {
+    let snapshot = take_snapshot();
+    PRESETS.insert(name.to_string(), snapshot.clone());
+
+    snapshot
+}
+
+/// Restore all values to the state stored with `save_preset`.
+///
+/// Returns `None` when no preset with the name is saved.
+pub fn load_preset(name: &str) -> Option<()> {
+    // Clone the snapshot so the map isn't locked while the callbacks are called
+    let snapshot = PRESETS.get(name)?.clone();
+    restore_snapshot(&snapshot);
+
+    Some(())
+}
+
+/// Remove a preset stored with `save_preset`.
+///
+/// Returns `false` when no preset with the name is saved.
+pub fn delete_preset(name: &str) -> bool {
+    PRESETS.remove(name).is_some()
+}
+
+/// The names of all saved presets, sorted alphabetically.
+pub fn presets() -> Vec<String> {
+    let mut names: Vec<String> = PRESETS.iter().map(|preset| preset.key().clone()).collect();
+    names.sort();
+
+    names
+}
+
+/// Save all values to a pretty-printed JSON file.
+///
+/// The file contains the values in the same format as the ones returned by `get_all`.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// let path = std::env::temp_dir().join("const-tweaker-doc.json");
+/// const_tweaker::save_to_file(&path)?;
+///
+/// const_tweaker::set_f64("VALUE", 0.5);
+/// const_tweaker::load_from_file(&path)?;
+/// assert_eq!(*VALUE, 0.0);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn save_to_file(path: impl AsRef<Path>) -> Result<(), TweakerError> {
+    let json = serde_json::to_string_pretty(&take_snapshot()).map_err(TweakerError::InvalidFile)?;
+
+    fs::write(path, json).map_err(TweakerError::Io)
+}
+
+/// Load the values from a JSON file written by `save_to_file`.
+///
+/// Values in the file that aren't registered are ignored with a warning. When one of the other
+/// values can't be loaded none of the values are changed.
+pub fn load_from_file(path: impl AsRef<Path>) -> Result<(), TweakerError> {
+    let json = fs::read_to_string(path).map_err(TweakerError::Io)?;
+    let snapshot: Snapshot = serde_json::from_str(&json).map_err(TweakerError::InvalidFile)?;
+
+    load_snapshot(&snapshot)
+}
+
+/// Load the values from a serialized `Snapshot`, like the ones written by `save_to_file`.
+///
+/// Values that aren't registered are ignored with a warning. When one of the other values can't
+/// be loaded none of the values are changed.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// let json = serde_json::to_string(&const_tweaker::take_snapshot())?;
+///
+/// const_tweaker::set_f64("VALUE", 0.5);
+/// const_tweaker::from_json_str(&json)?;
+/// assert_eq!(*VALUE, 0.0);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn from_json_str(json: &str) -> Result<(), TweakerError> {
+    let snapshot: Snapshot =
+        serde_json::from_str(json).map_err(|err| TweakerError::InvalidJson(err.to_string()))?;
+
+    load_snapshot(&snapshot)
+}
+
+/// Load all values of the snapshot or none when one of them is invalid.
+fn load_snapshot(snapshot: &Snapshot) -> Result<(), TweakerError> {
+    // Check all values before storing any of them, so either all or none are loaded
+    let mut updates = Vec::with_capacity(snapshot.values.len());
+    for (key, json) in snapshot.values.iter() {
+        match snapshot_update(key, json) {
+            Ok(Some(field)) => updates.push((key, field)),
+            Ok(None) => (),
+            Err(UpdateError::Missing) => log::warn!("Ignoring unknown value {}", key),
+            Err(err) => {
+                return Err(TweakerError::InvalidValue {
+                    key: key.to_string(),
+                    reason: err.to_string(),
+                })
+            }
+        }
+    }
+    updates
+        .into_iter()
+        .for_each(|(key, field)| store_field(key, field));
+
+    Ok(())
+}
+
+/// The prefix of the environment variables overriding values.
+const ENV_PREFIX: &str = "CONST_TWEAKER_";
+
+/// Override values with the environment variables starting with `CONST_TWEAKER_`.
+///
+/// The rest of the variable name is the key of the value, so `CONST_TWEAKER_GRAVITY=19.6`
+/// overrides the value `GRAVITY`. Values are only registered the first time they are read, so
+/// variables for values that aren't registered yet are ignored with a warning. When one of the
+/// other variables can't be applied none of the values are changed.
+///
+/// Returns the amount of values that were overridden.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// std::env::set_var("CONST_TWEAKER_VALUE", "0.5");
+/// assert_eq!(const_tweaker::apply_env_overrides()?, 1);
+/// assert_eq!(*VALUE, 0.5);
+/// # Ok::<(), const_tweaker::TweakerError>(())
+/// ```
+pub fn apply_env_overrides() -> Result<usize, TweakerError> {
+    // Check all values before storing any of them, so either all or none are overridden
+    let mut updates = Vec::new();
+    for (name, text) in std::env::vars_os() {
+        let (name, text) = match (name.into_string(), text.into_string()) {
+            (Ok(name), Ok(text)) => (name, text),
+            _ => continue,
+        };
+        let key = match name.strip_prefix(ENV_PREFIX) {
+            Some(key) => key.to_string(),
+            None => continue,
+        };
+
+        match env_update(&key, &text) {
+            Ok(field) => updates.push((key, field)),
+            Err(UpdateError::Missing) => {
+                log::warn!("Ignoring {} because {} isn't registered", name, key)
+            }
+            Err(err) => {
+                return Err(TweakerError::InvalidValue {
+                    key,
+                    reason: err.to_string(),
+                })
+            }
+        }
+    }
+
+    let count = updates.len();
+    updates
+        .into_iter()
+        .for_each(|(key, field)| store_field(&key, field));
+
+    Ok(count)
+}
+
+/// Parse the new value from the text of an environment variable & check it.
+fn env_update(key: &str, text: &str) -> Result<Field, UpdateError> {
+    let mut parsed = DATA.get(key).ok_or(UpdateError::Missing)?.value.clone();
+    parsed.set_str(text).map_err(UpdateError::InvalidValue)?;
+
+    updated_field(key, parsed.type_name(), |field| *field = parsed)
+}
+
+/// Describe all registered values as CSV, sorted by key.
+///
+/// The columns are `key,type,default,current,min,max,description,locked`. The values are in the
+/// same format as the environment variables of `apply_env_overrides`, the bounds are empty for
+/// the types without bounds.
+///
+/// ```rust
+/// #[const_tweaker::tweak(min = 0.0, max = 1.0)]
+/// const VALUE: f64 = 0.5;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.5);
+///
+/// assert_eq!(
+///     const_tweaker::export_csv(),
+///     "key,type,default,current,min,max,description,locked\nVALUE,f64,0.5,0.5,0.0,1.0,,false\n"
+/// );
+/// ```
+pub fn export_csv() -> String {
+    let mut csv = String::from("key,type,default,current,min,max,description,locked\n");
+    for info in enumerate_tweaks() {
+        let row = [
+            info.key.to_string(),
+            info.type_name.to_string(),
+            json_to_text(&info.default),
+            json_to_text(&info.current),
+            info.min.as_ref().map(json_to_text).unwrap_or_default(),
+            info.max.as_ref().map(json_to_text).unwrap_or_default(),
+            info.description.unwrap_or_default().to_string(),
+            info.locked.to_string(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Set the values from the `key` & `current` columns of CSV written by `export_csv`.
+///
+/// The other columns are ignored. Values that aren't registered are ignored with a warning.
+/// When one of the other values can't be applied none of the values are changed.
+///
+/// Returns the amount of values that were set.
+pub fn import_csv(csv: &str) -> Result<usize, TweakerError> {
+    let mut rows = parse_csv(csv)
+        .map_err(TweakerError::InvalidCsv)?
+        .into_iter();
+    let header = rows.next().unwrap_or_default();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| TweakerError::InvalidCsv(format!("missing the {} column", name)))
+    };
+    let (key_column, current_column) = (column("key")?, column("current")?);
+
+    // Check all values before storing any of them, so either all or none are set
+    let mut updates = Vec::new();
+    for row in rows {
+        let (key, text) = match (row.get(key_column), row.get(current_column)) {
+            (Some(key), Some(text)) => (key, text),
+            _ => {
+                return Err(TweakerError::InvalidCsv(format!(
+                    "row {:?} is too short",
+                    row
+                )))
+            }
+        };
+
+        match env_update(key, text) {
+            Ok(field) => updates.push((key.to_string(), field)),
+            Err(UpdateError::Missing) => log::warn!("Ignoring unknown value {}", key),
+            Err(err) => {
+                return Err(TweakerError::InvalidValue {
+                    key: key.to_string(),
+                    reason: err.to_string(),
+                })
+            }
+        }
+    }
+
+    let count = updates.len();
+    updates
+        .into_iter()
+        .for_each(|(key, field)| store_field(&key, field));
+
+    Ok(count)
+}
+
+/// Convert a value to the text used by `export_csv`, strings aren't quoted.
+fn json_to_text(json: &serde_json::Value) -> String {
+    match json {
+        serde_json::Value::String(text) => text.clone(),
+        json => json.to_string(),
+    }
+}
+
+/// Quote a CSV field when it contains a separator, a quote or a line break.
+fn csv_field(text: &str) -> String {
+    if text.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Split CSV into rows of fields, quoted fields can contain separators & line breaks.
+fn parse_csv(csv: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(character) = chars.next() {
+        match (quoted, character) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, character) => field.push(character),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') => (),
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, character) => field.push(character),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    // The last line doesn't have to end with a line break
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// How often a watched file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a watched file must be unchanged before it's loaded, so it's not read while an editor
+/// is still writing it.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Handle to a file watched with `watch_file`, the file is no longer watched when it's dropped.
+#[must_use = "the file is no longer watched when the handle is dropped"]
+#[derive(Debug)]
+pub struct WatchHandle {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        // Disconnecting the channel stops the thread
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Load the values from a JSON file with `load_from_file` every time the file is modified.
+///
+/// The file is polled for changes in a new thread and only loaded after it didn't change for
+/// 200 milliseconds. Reloads are logged as info & errors loading the file as errors, see
+/// `examples/hot_reload.rs` for the complete workflow.
+///
+/// ```rust,no_run
+/// let _watcher = const_tweaker::watch_file("tweaks.json")?;
+/// # Ok::<(), const_tweaker::TweakerError>(())
+/// ```
+pub fn watch_file(path: impl AsRef<Path>) -> Result<WatchHandle, TweakerError> {
+    let path = path.as_ref().to_path_buf();
+    let mut loaded = file_version(&path).map_err(TweakerError::Io)?;
+
+    let (stop, stopped) = mpsc::channel();
+    let thread = thread::Builder::new()
+        .name("const-tweaker-watch".to_string())
+        .spawn(move || {
+            // The changed version of the file & when it was first seen
+            let mut pending = None;
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(WATCH_INTERVAL) {
+                let version = match file_version(&path) {
+                    Ok(version) => version,
+                    // The file might be replaced by an editor, so try again later
+                    Err(_) => continue,
+                };
+
+                pending = match pending {
+                    _ if version == loaded => None,
+                    Some((pending_version, since)) if pending_version == version => {
+                        if Instant::now().duration_since(since) < WATCH_DEBOUNCE {
+                            Some((pending_version, since))
+                        } else {
+                            match load_from_file(&path) {
+                                Ok(()) => log::info!("Reloaded values from {}", path.display()),
+                                Err(err) => {
+                                    log::error!("Could not reload {}: {}", path.display(), err)
+                                }
+                            }
+                            loaded = version;
+                            None
+                        }
+                    }
+                    _ => Some((version, Instant::now())),
+                };
+            }
+        })
+        .map_err(|err| TweakerError::RuntimeError(err.to_string()))?;
+
+    Ok(WatchHandle {
+        stop: Some(stop),
+        thread: Some(thread),
+    })
+}
+
+/// The ID of the next handle returned by `record_changes_to_file`.
+static NEXT_RECORDER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A line in a file written by `record_changes_to_file`.
+#[derive(Debug, Deserialize)]
+struct RecordedChange {
+    /// When the value changed in milliseconds since the Unix epoch.
+    ts: u64,
+    key: String,
+    value: serde_json::Value,
+}
+
+/// Handle to a file changes are written to by `record_changes_to_file`, the file is closed when
+/// it's dropped.
+#[must_use = "the changes are no longer recorded when the handle is dropped"]
+#[derive(Debug)]
+pub struct RecordHandle {
+    id: u64,
+}
+
+impl RecordHandle {
+    /// Stop recording the changes & close the file.
+    pub fn stop(self) {
+        // Dropping the handle closes the file
+    }
+}
+
+impl Drop for RecordHandle {
+    fn drop(&mut self) {
+        RECORDERS
+            .lock()
+            .expect("Recorders lock is poisoned")
+            .retain(|(id, _)| *id != self.id);
+    }
+}
+
+/// Append every value change to a newline-delimited JSON file until the handle is stopped.
+///
+/// Every line is an object with the time in milliseconds since the Unix epoch, the key & the new
+/// value in the same format as the `value` returned by `get_all`:
+/// ```json
+/// { "ts": 1589480000000, "key": "GRAVITY", "value": 19.6 }
+/// ```
+///
+/// The file can be replayed with `replay_from_file`.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// let path = std::env::temp_dir().join("const-tweaker-record-doc.jsonl");
+/// # std::fs::remove_file(&path).ok();
+/// let recorder = const_tweaker::record_changes_to_file(&path)?;
+/// const_tweaker::set_f64("VALUE", 0.5);
+/// recorder.stop();
+///
+/// const_tweaker::set_f64("VALUE", 0.0);
+/// const_tweaker::replay_from_file(&path, 1.0)?;
+/// assert_eq!(*VALUE, 0.5);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn record_changes_to_file(path: impl AsRef<Path>) -> Result<RecordHandle, TweakerError> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(TweakerError::Io)?;
+
+    let id = NEXT_RECORDER_ID.fetch_add(1, Ordering::SeqCst);
+    RECORDERS
+        .lock()
+        .expect("Recorders lock is poisoned")
+        .push((id, LineWriter::new(file)));
+
+    Ok(RecordHandle { id })
+}
+
+/// Write a value change to the files of `record_changes_to_file`.
+fn record_to_files(key: &str, value: &serde_json::Value) {
+    let mut recorders = RECORDERS.lock().expect("Recorders lock is poisoned");
+    if recorders.is_empty() {
+        return;
+    }
+
+    let ts = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64);
+    let line = json!({ "ts": ts, "key": key, "value": value }).to_string();
+
+    // Files that can't be written to anymore are closed
+    recorders.retain_mut(|(_, file)| match writeln!(file, "{}", line) {
+        Ok(()) => true,
+        Err(err) => {
+            log::error!("Could not record the change of {}: {}", key, err);
+            false
+        }
+    });
+}
+
+/// Replay the value changes of a file written by `record_changes_to_file`.
+///
+/// The changes are applied with the same time between them as when they were recorded, divided by
+/// the speed. So a speed of `2.0` replays them twice as fast. This blocks until all changes are
+/// applied. Changes of values that aren't registered are ignored with a warning. The speed has to
+/// be a finite number larger than zero.
+pub fn replay_from_file(path: impl AsRef<Path>, speed: f64) -> Result<(), TweakerError> {
+    if !(speed > 0.0 && speed.is_finite()) {
+        return Err(TweakerError::InvalidSpeed(speed));
+    }
+
+    // Read all changes first, so nothing is replayed from an invalid file
+    let changes = fs::read_to_string(path)
+        .map_err(TweakerError::Io)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str::<RecordedChange>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(TweakerError::InvalidFile)?;
+
+    let first_ts = match changes.first() {
+        Some(change) => change.ts,
+        None => return Ok(()),
+    };
+    let start = Instant::now();
+    for change in changes {
+        let offset = Duration::from_millis(change.ts.saturating_sub(first_ts)).div_f64(speed);
+        if let Some(wait) = (start + offset).checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+
+        // The line has the value in the same format as the updates
+        let line = json!({ "value": change.value });
+        match json_update(&change.key, &line) {
+            Ok(field) => store_field(&change.key, field),
+            Err(UpdateError::Missing) => log::warn!("Ignoring unknown value {}", change.key),
+            Err(err) => {
+                return Err(TweakerError::InvalidValue {
+                    key: change.key,
+                    reason: err.to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The modification time & size of a file, used to detect changes.
+fn file_version(path: &Path) -> io::Result<(SystemTime, u64)> {
+    let metadata = fs::metadata(path)?;
+
+    Ok((metadata.modified()?, metadata.len()))
+}
+
+/// Parse a value from a snapshot & check it, returns `None` when the value didn't change.
+fn snapshot_update(key: &str, json: &serde_json::Value) -> Result<Option<Field>, UpdateError> {
+    let current = DATA.get(key).ok_or(UpdateError::Missing)?.value.clone();
+    let type_name = json.get("type").and_then(|type_name| type_name.as_str());
+    if type_name != Some(current.type_name()) {
+        return Err(UpdateError::TypeMismatch {
+            expected: type_name.unwrap_or_default().to_string(),
+            actual: current.type_name(),
+        });
+    }
+
+    let updated = json_update(key, json)?;
+    if updated.value_to_json() == current.value_to_json() {
+        Ok(None)
+    } else {
+        Ok(Some(updated))
+    }
+}
+
+/// Register a subscriber for a value, the unwrap function returns `None` when the type mismatches.
+fn watch<T: Send + 'static>(key: &str, unwrap: fn(&Field) -> Option<T>) -> Option<Receiver<T>> {
+    let key = {
+        let entry = DATA.get(key)?;
+        unwrap(&entry.value)?;
+
+        *entry.key()
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    WATCHERS
+        .entry(key)
+        .or_default()
+        .push(Box::new(move |field| match unwrap(field) {
+            Some(value) => sender.send(value).is_ok(),
+            None => false,
+        }));
+
+    Some(receiver)
+}
+
+/// Call a custom function every time a value changes.
+///
+/// The function is called with the key, the old value & the new value, the values are in the
+/// same format as the `value` returned by `get_all`. It replaces the previously set function.
+/// Every change is also logged with the `log` crate at the `info` level, including the address of
+/// the client when it's changed from the web interface.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, 0.0);
+///
+/// const_tweaker::set_log_fn(|key, old_value, new_value| {
+///     println!("{} changed from {} to {}", key, old_value, new_value);
+/// });
+/// const_tweaker::set_f64("VALUE", 0.5);
+/// ```
+pub fn set_log_fn(f: impl Fn(&str, serde_json::Value, serde_json::Value) + Send + Sync + 'static) {
+    *LOG_FN.write().expect("Log function lock is poisoned") = Some(Arc::new(f));
+}
+
+/// Call all callbacks & notify all subscribers registered for the key with the new field.
+///
+/// The field must not be borrowed from the map, because the callbacks might read the value.
+fn notify_change(key: &str, old: &Field, field: &Field) {
+    if let Some(mut entry) = DATA.get_mut(key) {
+        entry.change_count += 1;
+    }
+
+    let (old_value, new_value) = (old.value_to_json(), field.value_to_json());
+    let source_ip = source_ip();
+    match source_ip {
+        Some(ip) => log::info!(
+            "tweak changed: {} from {} to {} by {}",
+            key,
+            old_value,
+            new_value,
+            ip
+        ),
+        None => log::info!("tweak changed: {} from {} to {}", key, old_value, new_value),
+    }
+    record_change(key, &old_value, &new_value, source_ip);
+    record_to_files(key, &new_value);
+    #[cfg(feature = "metrics")]
+    CHANGE_COUNT.fetch_add(1, Ordering::SeqCst);
+    // Clone the function so the lock isn't held while it's called, it might replace itself
+    let log_fn = LOG_FN
+        .read()
+        .expect("Log function lock is poisoned")
+        .clone();
+    if let Some(log_fn) = log_fn {
+        log_fn(key, old_value, new_value);
+    }
+
+    // Clone the callbacks so the map isn't locked while they're called, they might read other
+    // values or change the value again
+    let callbacks = CALLBACKS
+        .get(key)
+        .map(|callbacks| callbacks.clone())
+        .unwrap_or_default();
+    callbacks.iter().for_each(|callback| callback(field));
+
+    // Remove the subscribers of which the receiver is dropped
+    if let Some(mut watchers) = WATCHERS.get_mut(key) {
+        watchers.retain(|watcher| watcher(field));
+    }
+
+    #[cfg(feature = "web")]
+    web::broadcast_event(key, field);
+}
+
+/// Add a value change to the change log, removing the oldest entries when it's full.
+fn record_change(
+    key: &str,
+    old_value: &serde_json::Value,
+    new_value: &serde_json::Value,
+    source_ip: Option<IpAddr>,
+) {
+    let mut change_log = CHANGE_LOG.lock().expect("Change log lock is poisoned");
+    change_log.push_back(ChangeLogEntry {
+        timestamp: SystemTime::now(),
+        key: key.to_string(),
+        old_value: old_value.clone(),
+        new_value: new_value.clone(),
+        source_ip,
+    });
+
+    let size = CHANGE_LOG_SIZE.load(Ordering::SeqCst);
+    while change_log.len() > size {
+        change_log.pop_front();
+    }
+}
+
+/// The address of the client changing a value from the web interface.
+fn source_ip() -> Option<IpAddr> {
+    #[cfg(feature = "web")]
+    {
+        web::peer_ip()
+    }
+    #[cfg(not(feature = "web"))]
+    {
+        None
+    }
+}
+
+/// Get the most recent value changes, newest first.
+///
+/// The amount of changes kept is set with `change_log_size` in the
+/// [`TweakerConfig`](struct.TweakerConfig.html).
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const VALUE: bool = false;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*VALUE, false);
+///
+/// const_tweaker::set_bool("VALUE", true);
+///
+/// let change = &const_tweaker::change_log()[0];
+/// assert_eq!(change.key, "VALUE");
+/// assert_eq!(change.old_value, serde_json::json!(false));
+/// assert_eq!(change.new_value, serde_json::json!(true));
+/// assert_eq!(change.source_ip, None);
+/// ```
+pub fn change_log() -> Vec<ChangeLogEntry> {
+    CHANGE_LOG
+        .lock()
+        .expect("Change log lock is poisoned")
+        .iter()
+        .rev()
+        .cloned()
+        .collect()
+}
+
+/// The group of the values that aren't assigned to a group & of which the file is unknown.
+const DEFAULT_GROUP: &str = "General";
+
+/// The file name without the extension, or the name of the directory for `mod.rs` files.
+fn file_group(file: &'static str) -> Option<&'static str> {
+    let path = Path::new(file);
+    match path.file_stem()?.to_str()? {
+        "mod" => path.parent()?.file_name()?.to_str(),
+        stem => Some(stem),
+    }
+}
+
+/// The names of all groups that contain values, sorted alphabetically.
+///
+/// Values without a group are grouped by the name of the file they are declared in.
+///
+/// ```rust
+/// #[const_tweaker::tweak(group = "Physics")]
+/// const GRAVITY: f64 = 0.0;
+///
+/// // The value is registered the first time it's read
+/// assert_eq!(*GRAVITY, 0.0);
+///
+/// assert_eq!(const_tweaker::groups(), vec!["Physics"]);
+/// ```
+pub fn groups() -> Vec<&'static str> {
+    DATA.iter()
+        .map(|entry| entry.group_name())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// The javascript call to send the updated data.
+fn send(key: &str, look_for: &str, data_type: &str) -> String {
+    format!("send('{}', {}, '{}')", key, look_for, data_type)
+}
+
+/// The javascript call to send the updated data after the slider stopped moving.
+fn send_debounced(key: &str, look_for: &str, data_type: &str) -> String {
+    format!("send_debounced('{}', {}, '{}')", key, look_for, data_type)
+}
+
+/// The reason a value couldn't be changed.
+#[derive(Debug, Clone, PartialEq)]
+enum UpdateError {
+    /// There's no value registered with the key.
+    Missing,
+    /// The registered value has a different type.
+    TypeMismatch {
+        expected: String,
+        actual: &'static str,
+    },
+    /// The new value can't be converted to the type of the registered value.
+    InvalidValue(String),
+    /// The value is locked.
+    Locked,
+    /// The new value is outside of the bounds.
+    OutOfRange(String),
+}
+
+impl Display for UpdateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            UpdateError::Missing => write!(f, "the value doesn't exist"),
+            UpdateError::TypeMismatch { expected, actual } => {
+                write!(f, "the value is a {}, not a {}", actual, expected)
+            }
+            UpdateError::InvalidValue(err) | UpdateError::OutOfRange(err) => write!(f, "{}", err),
+            UpdateError::Locked => write!(f, "the value is locked"),
+        }
+    }
+}
+
+/// Apply an update to a copy of the field, the copy is returned when it's within the bounds.
+fn updated_field(
+    key: &str,
+    type_name: &str,
+    update: impl FnOnce(&mut Field),
+) -> Result<Field, UpdateError> {
+    let entry = DATA.get(key).ok_or(UpdateError::Missing)?;
+    if entry.value.type_name() != type_name {
+        return Err(UpdateError::TypeMismatch {
+            expected: type_name.to_string(),
+            actual: entry.value.type_name(),
+        });
+    }
+    if entry.locked {
+        return Err(UpdateError::Locked);
+    }
+
+    let mut updated = entry.value.clone();
+    update(&mut updated);
+    match updated.range_error() {
+        Some(err) => Err(UpdateError::OutOfRange(err)),
+        None => Ok(updated),
+    }
+}
+
+/// Store a field that's checked with `updated_field`.
+///
+/// The callbacks & subscribers are notified of the new value.
+fn store_field(key: &str, field: Field) {
+    let old = match DATA.get_mut(key) {
+        Some(mut entry) => std::mem::replace(&mut entry.value, field.clone()),
+        None => return,
+    };
+    notify_change(key, &old, &field);
+}
+
+/// Store multiple fields that are checked with `updated_field`.
+///
+/// All fields are stored before the callbacks & subscribers are notified, so they never see a mix
+/// of old & new values.
+fn store_fields<'a>(updates: impl IntoIterator<Item = (&'a str, Field)>) {
+    let changes: Vec<(&str, Field, Field)> = updates
+        .into_iter()
+        .filter_map(|(key, field)| {
+            let mut entry = DATA.get_mut(key)?;
+            let old = std::mem::replace(&mut entry.value, field.clone());
+            Some((key, old, field))
+        })
+        .collect();
+
+    changes
+        .iter()
+        .for_each(|(key, old, field)| notify_change(key, old, field));
+}
+
+/// Apply an update to a copy of the field and only store it when it's within the bounds.
+///
+/// The callbacks & subscribers are notified of the new value.
+fn set_field(
+    key: &str,
+    type_name: &str,
+    update: impl FnOnce(&mut Field),
+) -> Result<(), UpdateError> {
+    let updated = updated_field(key, type_name, update)?;
+    store_field(key, updated);
+
+    Ok(())
+}
+
+/// Apply an update to the field while it's locked in the map, so concurrent updates aren't lost.
+///
+/// The callbacks & subscribers are notified of the new value, which is returned.
+fn alter_field(
+    key: &str,
+    type_name: &str,
+    update: impl FnOnce(&mut Field),
+) -> Result<Field, UpdateError> {
+    let (old, updated) = {
+        let mut entry = DATA.get_mut(key).ok_or(UpdateError::Missing)?;
+        if entry.value.type_name() != type_name {
+            return Err(UpdateError::TypeMismatch {
+                expected: type_name.to_string(),
+                actual: entry.value.type_name(),
+            });
+        }
+        if entry.locked {
+            return Err(UpdateError::Locked);
+        }
+
+        let mut updated = entry.value.clone();
+        update(&mut updated);
+        if let Some(err) = updated.range_error() {
+            return Err(UpdateError::OutOfRange(err));
+        }
+        (
+            std::mem::replace(&mut entry.value, updated.clone()),
+            updated,
+        )
+    };
+    // The entry isn't locked anymore, so the callbacks can read the value
+    notify_change(key, &old, &updated);
 
-    Response::new(200)
+    Ok(updated)
 }
 
-async fn handle_set_bool(mut request: Request<()>) -> Response {
-    let post_data: PostData<bool> = request.body_json().await.expect("Could not decode JSON");
-    DATA.get_mut(&*post_data.key)
-        .expect("Could not get item from map")
-        .set_bool(post_data.value);
+/// Parse the new value from a JSON object containing a `value` field & check it.
+fn json_update(key: &str, json: &serde_json::Value) -> Result<Field, UpdateError> {
+    let mut parsed = DATA.get(key).ok_or(UpdateError::Missing)?.value.clone();
+    let value = json
+        .get("value")
+        .ok_or_else(|| UpdateError::InvalidValue("missing the value field".to_string()))?;
+    parsed.set_json(value).map_err(UpdateError::InvalidValue)?;
 
-    Response::new(200)
+    updated_field(key, parsed.type_name(), |field| *field = parsed)
 }
@@ -1,18 +1,718 @@
+// Remember the token from the URL so it can be sent with every request
+var token = new URLSearchParams(location.search).get('token');
+if (token) {
+	sessionStorage.setItem('token', token);
+} else {
+	token = sessionStorage.getItem('token');
+}
+
+function auth_headers(headers) {
+	if (token) {
+		headers['Authorization'] = 'Bearer ' + token;
+	}
+
+	return headers;
+}
+
+// Call the function when it hasn't been called again for the delay in milliseconds
+function debounce(fn, delay) {
+	var timeout;
+	var debounced = function() {
+		var args = arguments;
+		clearTimeout(timeout);
+		timeout = setTimeout(() => fn.apply(this, args), delay);
+	};
+	debounced.cancel = () => clearTimeout(timeout);
+
+	return debounced;
+}
+
+// The debounced send functions per value
+var debounced_sends = {};
+
+// Only send the value of a slider that's being dragged when it stopped moving
+function send_debounced(source, value, data_type) {
+	update_label(source, value, data_type);
+	if (!(source in debounced_sends)) {
+		debounced_sends[source] = debounce(send, debounce_ms);
+	}
+	debounced_sends[source](source, value, data_type);
+}
+
 async function send(source, value, data_type) {
-	// Change the label
-	var label_element = document.getElementById(source + '_label');
-	if (label_element) {
-		label_element.innerHTML = value;
+	// Don't send incomplete numbers, for example when a number input is cleared
+	if (typeof value === 'number' && isNaN(value)) {
+		return;
+	}
+
+	// The value is sent now, so a debounced send would only send it again
+	if (source in debounced_sends) {
+		debounced_sends[source].cancel();
 	}
 
+	update_label(source, value, data_type);
+	record_undo(source);
+
 	// Make the request
 	fetch('/set/' + data_type, {
 		method: 'POST',
-		headers: {
+		headers: auth_headers({
 			'Content-Type': 'application/json'
-		},
+		}),
 		body: JSON.stringify({key: source, value: value})
 	}).catch(err => {
 		document.getElementById('status').textContent = 'HTTP Error: ' + err;
 	});
 }
+
+async function reset_all() {
+	post_and_reload('/api/reset');
+}
+
+async function reset(source) {
+	post_and_reload('/api/reset/' + encodeURIComponent(source));
+}
+
+// Reload the page after the request so all widgets show the restored values
+function post_and_reload(url) {
+	fetch(url, {
+		method: 'POST',
+		headers: auth_headers({})
+	}).then(() => {
+		location.reload();
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}
+
+// Move a slider to the value, taking the logarithmic scale into account
+function set_slider(slider, value) {
+	slider.value = slider.dataset.scale === 'log' ? Math.log10(value) : value;
+}
+
+// Round a float to the configured amount of significant digits, removing trailing zeros
+function format_float(value) {
+	return String(Number(value.toPrecision(significant_digits)));
+}
+
+// Send the value of the number input next to a slider & move the slider to it
+function send_number(source, input, data_type) {
+	var value = Number(input.value);
+	if (input.value === '' || isNaN(value)) {
+		return;
+	}
+
+	set_slider(document.getElementById(source), value);
+	send(source, value, data_type);
+}
+
+for (var input of document.querySelectorAll('.float-number')) {
+	input.value = format_float(Number(input.value));
+}
+
+function update_label(source, value, data_type) {
+	// Don't overwrite a number while it's being typed
+	var number_element = document.getElementById(source + '_number');
+	if (number_element && document.activeElement !== number_element) {
+		number_element.value = format_float(Number(value));
+	}
+
+	var label_element = document.getElementById(source + '_label');
+	if (label_element) {
+		if (data_type === 'u8') {
+			// Also show bytes as hexadecimal
+			label_element.textContent = value + ' (0x' + value.toString(16).toUpperCase().padStart(2, '0') + ')';
+		} else {
+			label_element.textContent = value;
+		}
+	}
+}
+
+// When the values were changed during this session, used for sorting
+var modified = {};
+
+// Update the widgets when a value is changed somewhere else
+new EventSource(token ? '/events?token=' + encodeURIComponent(token) : '/events').onmessage = event => {
+	var change = JSON.parse(event.data);
+	modified[change.key] = Date.now();
+	current_values[change.key] = change.value;
+	update_modified(change.key);
+	var changes = document.getElementById(change.key + '_changes');
+	if (changes) {
+		changes.textContent = change.changes;
+	}
+	var element = document.getElementById(change.key);
+	if (!element) {
+		update_struct(change.key, change.value);
+		update_vec(change.key, change.value);
+		// Read-only values only have a label
+		update_label(change.key, change.value, change.type);
+		return;
+	}
+
+	// Values that can be disabled have an extra checkbox
+	var enabled = document.getElementById(change.key + '::enabled');
+	if (enabled) {
+		enabled.checked = change.value !== null;
+		element.disabled = change.value === null;
+		if (change.value === null) {
+			return;
+		}
+	}
+
+	if (element.type === 'checkbox') {
+		element.checked = change.value;
+	} else if (element.type === 'color') {
+		update_color(change.key, change.value);
+	} else if (element.type === 'range') {
+		set_slider(element, change.value);
+	} else {
+		element.value = change.value;
+	}
+	update_label(change.key, change.value, change.type);
+};
+
+// Show the most recent changes while the panel is opened
+setInterval(() => {
+	if (!document.getElementById('log_panel').open) {
+		return;
+	}
+
+	fetch('/log', {
+		headers: auth_headers({})
+	}).then(response => response.json()).then(entries => {
+		var log = document.getElementById('log');
+		log.textContent = '';
+		entries.forEach(entry => {
+			var row = log.insertRow();
+			[
+				new Date(entry.timestamp).toLocaleTimeString(),
+				entry.key,
+				JSON.stringify(entry.old_value),
+				JSON.stringify(entry.new_value),
+				entry.source_ip || ''
+			].forEach(text => {
+				row.insertCell().textContent = text;
+			});
+		});
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}, 1000);
+
+// Set the value of a custom type, used by the widgets rendered by `Tweakable::render_html`
+function send_json(source, value) {
+	record_undo(source);
+	post_json(source, value);
+}
+
+// Set a value of any type without recording it in the undo history
+function post_json(source, value) {
+	fetch('/api/values', {
+		method: 'POST',
+		headers: auth_headers({
+			'Content-Type': 'application/json'
+		}),
+		body: JSON.stringify({[source]: {value: value}})
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}
+
+// Send all fields of a struct deriving `Tweakable`
+function send_struct(source) {
+	var value = {};
+	for (var input of document.getElementsByName(source)) {
+		var field = input.id.substr(source.length + 2);
+		if (input.type === 'checkbox') {
+			value[field] = input.checked;
+		} else if (input.type === 'number') {
+			value[field] = parseFloat(input.value);
+			// Don't send incomplete numbers, for example when a number input is cleared
+			if (isNaN(value[field])) {
+				return;
+			}
+		} else {
+			value[field] = input.value;
+		}
+	}
+
+	send_json(source, value);
+}
+
+// Update the inputs of the fields of a struct deriving `Tweakable`
+function update_struct(source, value) {
+	if (typeof value !== 'object' || value === null) {
+		return;
+	}
+
+	for (var field in value) {
+		var input = document.getElementById(source + '::' + field);
+		if (!input) {
+			continue;
+		} else if (input.type === 'checkbox') {
+			input.checked = value[field];
+		} else {
+			input.value = value[field];
+		}
+	}
+}
+
+// Send a color as a '#RRGGBBAA' hex string
+function send_optional(source, type) {
+	var enabled = document.getElementById(source + '::enabled').checked;
+	var input = document.getElementById(source);
+	input.disabled = !enabled;
+	var value = type === 'bool' ? input.checked : parseFloat(input.value);
+	record_undo(source);
+
+	fetch('/set/optional_' + type, {
+		method: 'POST',
+		headers: auth_headers({
+			'Content-Type': 'application/json'
+		}),
+		// A disabled number without a value is enabled with zero
+		body: JSON.stringify({key: source, enabled: enabled, value: type === 'f64' && isNaN(value) ? 0 : value})
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}
+
+// The current elements of a Vec<f64> value
+function vec_values(source) {
+	var inputs = document.getElementById(source + '::elements').getElementsByTagName('input');
+	return Array.from(inputs).map(input => parseFloat(input.value) || 0);
+}
+
+function send_vec(source, values) {
+	record_undo(source);
+
+	fetch('/set/vec_f64', {
+		method: 'POST',
+		headers: auth_headers({
+			'Content-Type': 'application/json'
+		}),
+		body: JSON.stringify({key: source, value: values || vec_values(source)})
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}
+
+// Add an element with the value zero or remove the last element
+function resize_vec(source, change) {
+	var values = vec_values(source);
+	if (change > 0) {
+		values.push(0);
+	} else {
+		values.pop();
+	}
+	send_vec(source, values);
+}
+
+// Show the elements of a Vec<f64> value, the inputs are recreated when the length changed
+function update_vec(source, values) {
+	var elements = document.getElementById(source + '::elements');
+	if (!elements || !Array.isArray(values)) {
+		return;
+	}
+
+	var inputs = elements.getElementsByTagName('input');
+	if (inputs.length !== values.length) {
+		elements.replaceChildren(...values.map((_, index) => {
+			var field = document.createElement('div');
+			field.className = 'field has-addons';
+			field.innerHTML = '<div class="control"><span class="button is-small is-static">' + index +
+				'</span></div><div class="control"><input type="number" class="input is-small" step="any"></div>';
+			var input = field.getElementsByTagName('input')[0];
+			input.id = source + '::' + index;
+			input.onchange = () => send_vec(source);
+			return field;
+		}));
+	}
+	values.forEach((value, index) => {
+		inputs[index].value = value;
+	});
+}
+
+function send_color(source) {
+	var alpha = Math.round(document.getElementById(source + '::a').value * 255);
+	var color = document.getElementById(source).value + alpha.toString(16).padStart(2, '0');
+	record_undo(source);
+
+	fetch('/set/color', {
+		method: 'POST',
+		headers: auth_headers({
+			'Content-Type': 'application/json'
+		}),
+		body: JSON.stringify({key: source, value: color})
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}
+
+// Update the color picker & alpha slider from the RGBA floats
+function update_color(source, value) {
+	document.getElementById(source).value = '#' + value.slice(0, 3)
+		.map(channel => Math.round(channel * 255).toString(16).padStart(2, '0'))
+		.join('');
+	document.getElementById(source + '::a').value = value[3];
+}
+
+// Only show the values of which the key, description or group contains the filter
+function filter_tweaks() {
+	var filter = document.getElementById('search').value.toLowerCase();
+	for (var group of document.getElementsByClassName('group')) {
+		var visible = 0;
+		for (var tweak of group.getElementsByClassName('tweak')) {
+			var matches = tweak.dataset.search.includes(filter);
+			tweak.style.display = matches ? '' : 'none';
+			visible += matches ? 1 : 0;
+		}
+		// Hide the groups without any matching values
+		group.style.display = visible > 0 ? '' : 'none';
+	}
+}
+
+function clear_filter() {
+	document.getElementById('search').value = '';
+	filter_tweaks();
+}
+
+// Restore the groups that were collapsed before the page was refreshed
+var collapsed_groups = new Set(JSON.parse(localStorage.getItem('collapsed_groups') || '[]'));
+for (var group of document.getElementsByClassName('group')) {
+	if (collapsed_groups.has(group.dataset.group)) {
+		group.open = false;
+	}
+
+	// Remember the groups that are collapsed
+	group.addEventListener('toggle', event => {
+		if (event.target.open) {
+			collapsed_groups.delete(event.target.dataset.group);
+		} else {
+			collapsed_groups.add(event.target.dataset.group);
+		}
+		localStorage.setItem('collapsed_groups', JSON.stringify([...collapsed_groups]));
+	});
+}
+
+function set_groups_open(open) {
+	for (var group of document.getElementsByClassName('group')) {
+		group.open = open;
+	}
+}
+
+// Show the state of the theme on the toggle
+function update_theme_toggle() {
+	var dark = document.body.classList.contains('dark');
+	var toggle = document.getElementById('theme_toggle');
+	toggle.setAttribute('aria-checked', dark);
+	// Show the sun in dark mode & the moon in light mode
+	toggle.textContent = dark ? '☀' : '☾';
+}
+update_theme_toggle();
+
+function toggle_theme() {
+	var dark = document.body.classList.toggle('dark');
+	localStorage.setItem('theme', dark ? 'dark' : 'light');
+	update_theme_toggle();
+}
+
+// Reorder the values within every group
+function sort_tweaks() {
+	var sort = document.getElementById('sort').value;
+	var by_key = (a, b) => a.dataset.key.localeCompare(b.dataset.key);
+	var compare = {
+		name: by_key,
+		name_desc: (a, b) => by_key(b, a),
+		type: (a, b) => a.dataset.type.localeCompare(b.dataset.type) || by_key(a, b),
+		// The groups are always sorted by name, so sort the values within them by name
+		group: by_key,
+		// Values that weren't changed are sorted last
+		modified: (a, b) => (modified[b.dataset.key] || 0) - (modified[a.dataset.key] || 0) || by_key(a, b)
+	}[sort];
+
+	for (var group of document.getElementsByClassName('group')) {
+		[...group.getElementsByClassName('tweak')]
+			.sort(compare)
+			.forEach(tweak => group.appendChild(tweak));
+	}
+}
+
+// Show a message for a few seconds
+var toast_timeout;
+function show_toast(message, is_error) {
+	var toast = document.getElementById('toast');
+	toast.textContent = message;
+	toast.className = 'notification ' + (is_error ? 'is-danger' : 'is-success');
+	toast.style.display = '';
+
+	clearTimeout(toast_timeout);
+	toast_timeout = setTimeout(() => {
+		toast.style.display = 'none';
+	}, 3000);
+}
+
+// Copy all values to the clipboard
+function copy_json() {
+	fetch('/api/snapshot', {
+		headers: auth_headers({})
+	}).then(response => response.json()).then(snapshot => {
+		return navigator.clipboard.writeText(JSON.stringify(snapshot, null, 2));
+	}).then(() => {
+		show_toast('Copied the values to the clipboard', false);
+	}).catch(err => {
+		show_toast('Could not copy the values: ' + err, true);
+	});
+}
+
+// Restore the values copied with `copy_json` from the clipboard
+function paste_json() {
+	navigator.clipboard.readText().then(text => {
+		var snapshot = JSON.parse(text);
+		var valid = typeof snapshot === 'object' && snapshot !== null && !Array.isArray(snapshot)
+			&& Object.values(snapshot).every(entry => typeof entry === 'object' && entry !== null
+				&& typeof entry.type === 'string' && 'value' in entry);
+		if (!valid) {
+			throw 'expected an object with a type & a value for every key';
+		}
+
+		return fetch('/api/snapshot', {
+			method: 'POST',
+			headers: auth_headers({
+				'Content-Type': 'application/json'
+			}),
+			body: JSON.stringify(snapshot)
+		});
+	}).then(response => {
+		if (!response.ok) {
+			return response.text().then(text => {
+				throw text;
+			});
+		}
+		show_toast('Restored the values from the clipboard', false);
+	}).catch(err => {
+		show_toast('Could not paste the values: ' + err, true);
+	});
+}
+
+// The current & default values as JSON, used to restore them with undo & redo and to highlight
+// the modified values
+var current_values = {};
+var default_values = {};
+fetch('/api/values', {
+	headers: auth_headers({})
+}).then(response => response.json()).then(values => {
+	for (var key in values) {
+		if (!(key in current_values)) {
+			current_values[key] = values[key].value;
+		}
+		default_values[key] = values[key].default;
+	}
+});
+
+// The values before & after the changes, newest last
+var undo_stack = [];
+var redo_stack = [];
+
+// Remember the value before it's changed
+function record_undo(source) {
+	var last = undo_stack[undo_stack.length - 1];
+	// Dragging a slider sends many changes, combine them into a single undo step
+	if (last && last.key === source && Date.now() - last.time < 1000) {
+		last.time = Date.now();
+		return;
+	}
+	if (!(source in current_values)) {
+		return;
+	}
+
+	undo_stack.push({key: source, value: current_values[source], time: Date.now()});
+	if (undo_stack.length > 50) {
+		undo_stack.shift();
+	}
+	redo_stack = [];
+	update_undo_badge();
+}
+
+// Restore the value of the last step & move the step to the other stack
+function undo_step(from, to) {
+	var step = from.pop();
+	if (!step) {
+		return;
+	}
+
+	to.push({key: step.key, value: current_values[step.key], time: 0});
+	post_json(step.key, step.value);
+	update_undo_badge();
+}
+
+function update_undo_badge() {
+	document.getElementById('undo_badge').textContent = 'Undo (' + undo_stack.length + ')';
+}
+
+document.addEventListener('keydown', event => {
+	// Keep the normal undo behavior of text inputs
+	if (!(event.ctrlKey || event.metaKey) || event.key.toLowerCase() !== 'z' || event.target.type === 'text') {
+		return;
+	}
+
+	event.preventDefault();
+	if (event.shiftKey) {
+		undo_step(redo_stack, undo_stack);
+	} else {
+		undo_step(undo_stack, redo_stack);
+	}
+});
+
+// Highlight the value when it differs from the default
+function update_modified(source) {
+	var tweak = document.querySelector('.tweak[data-key="' + CSS.escape(source) + '"]');
+	if (tweak && source in default_values) {
+		var modified = JSON.stringify(current_values[source]) !== JSON.stringify(default_values[source]);
+		tweak.classList.toggle('modified', modified);
+	}
+}
+
+function toggle_changes_only() {
+	document.body.classList.toggle('changes-only', document.getElementById('changes_only').checked);
+}
+
+// Lock or unlock a value so it can't be changed by accident
+function toggle_lock(source) {
+	var tweak = document.querySelector('.tweak[data-key="' + CSS.escape(source) + '"]');
+	var locked = tweak.dataset.locked === 'true';
+
+	fetch('/api/lock/' + encodeURIComponent(source), {
+		method: locked ? 'DELETE' : 'POST',
+		headers: auth_headers({})
+	}).then(response => {
+		if (!response.ok) {
+			throw response.status;
+		}
+		update_lock(tweak, !locked);
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}
+
+function update_lock(tweak, locked) {
+	tweak.dataset.locked = locked;
+	var button = tweak.querySelector('.lock');
+	button.textContent = locked ? '\u{1F512}' : '\u{1F513}';
+	button.title = locked ? 'Unlock' : 'Lock';
+	for (var input of tweak.querySelectorAll('input, select')) {
+		input.disabled = locked;
+	}
+}
+
+for (var tweak of document.querySelectorAll('.tweak[data-locked="true"]')) {
+	update_lock(tweak, true);
+}
+
+// Show the saved presets with buttons to load & delete them
+function update_presets() {
+	fetch('/api/presets', {
+		headers: auth_headers({})
+	}).then(response => response.json()).then(names => {
+		var presets = document.getElementById('presets');
+		presets.textContent = '';
+		names.forEach(name => {
+			var row = presets.insertRow();
+			row.insertCell().textContent = name;
+			var buttons = row.insertCell();
+			[['Load', load_preset], ['Delete', delete_preset]].forEach(([text, action]) => {
+				var button = document.createElement('button');
+				button.className = 'button is-small';
+				button.textContent = text;
+				button.onclick = () => action(name);
+				buttons.appendChild(button);
+			});
+		});
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+	});
+}
+update_presets();
+
+function save_preset() {
+	var name = document.getElementById('preset_name').value.trim();
+	if (!name) {
+		return;
+	}
+
+	preset_request('POST', name).then(() => {
+		document.getElementById('preset_name').value = '';
+		update_presets();
+	});
+}
+
+// Reload the page so all widgets show the restored values
+function load_preset(name) {
+	preset_request('GET', name).then(() => location.reload());
+}
+
+function delete_preset(name) {
+	preset_request('DELETE', name).then(update_presets);
+}
+
+function preset_request(method, name) {
+	return fetch('/api/presets/' + encodeURIComponent(name), {
+		method: method,
+		headers: auth_headers({})
+	}).then(response => {
+		if (!response.ok) {
+			throw response.status;
+		}
+	}).catch(err => {
+		document.getElementById('status').textContent = 'HTTP Error: ' + err;
+		throw err;
+	});
+}
+
+// Copy a URL that restores all values when it's opened
+function share_url() {
+	fetch('/api/snapshot', {
+		headers: auth_headers({})
+	}).then(response => response.text()).then(json => {
+		// Encode the JSON as UTF-8 first, `btoa` only accepts single byte characters
+		var binary = '';
+		new TextEncoder().encode(json).forEach(byte => {
+			binary += String.fromCharCode(byte);
+		});
+		var url = location.origin + location.pathname + '?state=' + encodeURIComponent(btoa(binary));
+		return navigator.clipboard.writeText(url);
+	}).then(() => {
+		show_toast('Copied the URL to the clipboard', false);
+	}).catch(err => {
+		show_toast('Could not copy the URL: ' + err, true);
+	});
+}
+
+// Restore the values from a URL created with `share_url`
+var shared_state = new URLSearchParams(location.search).get('state');
+if (shared_state) {
+	new Promise(resolve => {
+		var bytes = Uint8Array.from(atob(shared_state), character => character.charCodeAt(0));
+		resolve(new TextDecoder().decode(bytes));
+	}).then(json => fetch('/api/snapshot', {
+		method: 'POST',
+		headers: auth_headers({
+			'Content-Type': 'application/json'
+		}),
+		body: json
+	})).then(response => {
+		if (!response.ok) {
+			return response.text().then(text => {
+				throw text;
+			});
+		}
+
+		// Reload without the state so refreshing doesn't restore it again
+		var params = new URLSearchParams(location.search);
+		params.delete('state');
+		location.replace(location.pathname + (params.toString() ? '?' + params : ''));
+	}).catch(err => {
+		show_toast('Could not restore the shared values: ' + err, true);
+	});
+}
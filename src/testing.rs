@@ -0,0 +1,154 @@
+//! Helpers to change values in tests without running the web server.
+//!
+//! ```rust
+//! #[const_tweaker::tweak]
+//! const GRAVITY: f64 = 9.8;
+//!
+//! // The value is registered the first time it's read
+//! assert_eq!(*GRAVITY, 9.8);
+//!
+//! const_tweaker::testing::with_values(&[("GRAVITY", serde_json::json!(0.0))], || {
+//!     assert_eq!(*GRAVITY, 0.0);
+//! });
+//!
+//! // The value is restored afterwards
+//! assert_eq!(*GRAVITY, 9.8);
+//! ```
+
+use crate::{json_update, store_fields, Field, DATA};
+use serde_json::json;
+use std::{any::Any, cell::RefCell, collections::HashMap};
+
+thread_local! {
+    /// The values mocked with `mock_tweaks!` on this thread.
+    static MOCKS: RefCell<HashMap<&'static str, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Override values on the current thread until the end of the scope.
+///
+/// Reading a mocked value returns the mocked value without registering the value, other threads
+/// still see the registered value. This isolates tests running at the same time from each other.
+///
+/// ```rust
+/// #[const_tweaker::tweak]
+/// const GRAVITY: f64 = 9.8;
+///
+/// #[const_tweaker::tweak]
+/// const DEBUG_DRAW: bool = false;
+///
+/// {
+///     const_tweaker::mock_tweaks! { GRAVITY => 0.0, DEBUG_DRAW => true }
+///     assert_eq!(*GRAVITY, 0.0);
+///     assert!(*DEBUG_DRAW);
+/// }
+///
+/// assert_eq!(*GRAVITY, 9.8);
+/// ```
+#[macro_export]
+macro_rules! mock_tweaks {
+    ($($name:ident => $value:expr),* $(,)?) => {
+        // The guards remove the mocked values when they're dropped at the end of the scope
+        let _mock_guards = ($($name.mock($value, $crate::testing::mock_value),)*);
+    };
+}
+
+/// Override a value on the current thread until the guard is dropped, use `mock_tweaks!` instead.
+#[doc(hidden)]
+pub fn mock_value<T: 'static>(key: &'static str, value: T) -> MockGuard {
+    let previous = MOCKS.with(|mocks| mocks.borrow_mut().insert(key, Box::new(value)));
+
+    MockGuard { key, previous }
+}
+
+/// The mocked value on the current thread, `None` when it's not mocked.
+#[doc(hidden)]
+pub fn mocked_value<T: Clone + 'static>(key: &str) -> Option<T> {
+    MOCKS.with(|mocks| mocks.borrow().get(key)?.downcast_ref::<T>().cloned())
+}
+
+/// Restores the mocked value to the previous value when it's dropped.
+#[doc(hidden)]
+pub struct MockGuard {
+    key: &'static str,
+    previous: Option<Box<dyn Any>>,
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        // The thread local is already destroyed when the guard is dropped while the thread exits
+        let _ = MOCKS.try_with(|mocks| {
+            let mut mocks = mocks.borrow_mut();
+            match self.previous.take() {
+                Some(previous) => mocks.insert(self.key, previous),
+                None => mocks.remove(self.key),
+            }
+        });
+    }
+}
+
+/// Change a `f64` value, the callbacks & subscribers are notified of the new value.
+///
+/// Panics when no `f64` value with the key is registered, when the value is locked or when it's
+/// outside of the bounds.
+pub fn set_f64(key: &str, value: f64) {
+    assert!(
+        crate::set_f64(key, value),
+        "Could not set {} to {}",
+        key,
+        value
+    );
+}
+
+/// Change a `bool` value, the callbacks & subscribers are notified of the new value.
+///
+/// Panics when no `bool` value with the key is registered or when the value is locked.
+pub fn set_bool(key: &str, value: bool) {
+    assert!(
+        crate::set_bool(key, value),
+        "Could not set {} to {}",
+        key,
+        value
+    );
+}
+
+/// Set the values, run the function & restore the previous values, also when the function panics.
+///
+/// The values are in the same format as the `value` returned by `get_all`. The values have to be
+/// registered, which happens the first time they're read.
+///
+/// Panics when a value isn't registered, is locked or is invalid, before any value is changed.
+pub fn with_values(values: &[(&str, serde_json::Value)], f: impl FnOnce()) {
+    let updates: Vec<(&str, Field)> = values
+        .iter()
+        .map(
+            |(key, value)| match json_update(key, &json!({ "value": value })) {
+                Ok(field) => (*key, field),
+                Err(err) => panic!("Could not set {} to {}: {}", key, value, err),
+            },
+        )
+        .collect();
+
+    // The previous values are restored when the guard is dropped, which also happens when
+    // unwinding
+    let _restore = Restore {
+        previous: updates
+            .iter()
+            .filter_map(|(key, _)| Some((*key, DATA.get(*key)?.value.clone())))
+            .collect(),
+    };
+    store_fields(updates);
+
+    f();
+}
+
+/// Restores the values when it's dropped.
+struct Restore<'a> {
+    previous: Vec<(&'a str, Field)>,
+}
+
+impl Drop for Restore<'_> {
+    fn drop(&mut self) {
+        // Restore in reverse so a key that's set multiple times gets its original value
+        store_fields(self.previous.drain(..).rev());
+    }
+}
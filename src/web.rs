@@ -0,0 +1,2753 @@
+//! The web service to change the values from the browser.
+
+#[cfg(feature = "metrics")]
+use crate::CHANGE_COUNT;
+use crate::{
+    alter_field, apply_env_overrides, change_count, change_log, delete_preset, enumerate_tweaks,
+    export_csv, get_registered_keys, groups, json_update, list_json, presets, reset_value,
+    save_preset, set_field, set_locked, snapshot_update, store_field, store_fields, take_snapshot,
+    ChangeLogEntry, Field, Snapshot, TweakerError, UpdateError, CHANGE_LOG_SIZE, DATA, PRESETS,
+};
+use async_std::{
+    io::prelude::*,
+    net::{TcpListener, TcpStream},
+    task,
+};
+use futures::{
+    channel::oneshot,
+    future::{self, FutureObj},
+    task::{Spawn, SpawnError},
+    AsyncRead, AsyncWrite, FutureExt, Stream, StreamExt, TryStreamExt,
+};
+use horrorshow::{html, owned_html, Raw, Render};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    cell::Cell,
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    hash::{Hash, Hasher},
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr},
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    thread::{self, JoinHandle},
+    time::{Duration, UNIX_EPOCH},
+};
+use tide::{http::Method, server::Server, Middleware, Next, Request, Response};
+#[cfg(unix)]
+use {
+    async_std::os::unix::net::UnixListener,
+    percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+lazy_static::lazy_static! {
+    /// The connected server-sent event clients that are sent a message when a value changes.
+    static ref EVENT_CLIENTS: Mutex<Vec<async_channel::Sender<String>>> = Mutex::new(Vec::new());
+}
+
+async_std::task_local! {
+    /// The address of the client of the connection handled by the current task.
+    static PEER_IP: Cell<Option<IpAddr>> = Cell::new(None);
+}
+
+#[cfg(feature = "compression")]
+lazy_static::lazy_static! {
+    /// The hash of the last page that was compressed with the compressed page.
+    ///
+    /// The page only changes when a value changes, so most requests can reuse it.
+    static ref COMPRESSED_PAGE: Mutex<Option<(u64, Vec<u8>)>> = Mutex::new(None);
+}
+
+/// A struct used for deserializing the JSON data of a relative change.
+#[derive(Debug, Deserialize)]
+struct DeltaData {
+    key: String,
+    delta: f64,
+}
+
+/// A struct used for deserializing the JSON data of a request only containing the key.
+#[derive(Debug, Deserialize)]
+struct KeyData {
+    key: String,
+}
+
+/// A struct used for deserializing a value of a batch update.
+#[derive(Debug, Deserialize)]
+struct BatchUpdate {
+    key: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    value: serde_json::Value,
+}
+
+/// A struct used for deserializing POST request JSON data.
+#[derive(Debug, Deserialize)]
+struct PostData<T> {
+    key: String,
+    value: T,
+}
+
+impl ChangeLogEntry {
+    /// Convert the entry to JSON, the timestamp is in milliseconds since the UNIX epoch.
+    fn to_json(&self) -> serde_json::Value {
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        json!({
+            "timestamp": timestamp,
+            "key": self.key,
+            "old_value": self.old_value,
+            "new_value": self.new_value,
+            "source_ip": self.source_ip,
+        })
+    }
+}
+
+/// Configuration of the `const` tweaker web service.
+///
+/// ```rust
+/// use const_tweaker::TweakerConfig;
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// let config = TweakerConfig::builder().port(8080).build();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.host, IpAddr::V4(Ipv4Addr::LOCALHOST));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TweakerConfig {
+    /// The address the web server binds to, defaults to `127.0.0.1`.
+    ///
+    /// Binding to a non-loopback address such as `0.0.0.0` allows anyone on the network to see and
+    /// change the values, so only do this on a network you trust. Other machines also have to be
+    /// added to the `ip_allowlist`.
+    ///
+    /// IPv6 addresses are supported, use `::1` for the loopback address and `::` for all
+    /// interfaces. On dual-stack systems binding to `::` might also accept IPv4 connections,
+    /// depending on the operating system.
+    pub host: IpAddr,
+    /// The port the web server listens on, defaults to `9938`.
+    pub port: u16,
+    /// When set, every request must contain an `Authorization: Bearer <token>` header or a
+    /// `token` query parameter with this token, defaults to `None`.
+    ///
+    /// Open the web interface with `http://127.0.0.1:9938/?token=<token>`, the page sends the token
+    /// with all following requests. The token is sent as plain text because there's no HTTPS, so
+    /// this only protects against casual access on a network you trust.
+    pub auth_token: Option<String>,
+    /// The maximum size in bytes of a request body, larger requests are rejected, defaults to 1
+    /// MiB.
+    ///
+    /// Requests with a body need a `Content-Length` header, chunked requests are rejected with a
+    /// 411.
+    pub max_body_size: usize,
+    /// How long to wait for requests that are still being handled when shutting down, defaults to
+    /// 5 seconds.
+    pub shutdown_timeout: Duration,
+    /// The origin that's allowed to access the web server from another site, defaults to `*`.
+    pub allow_origin: String,
+    /// The addresses that are allowed to connect, connections from other addresses are refused
+    /// with a 403 response, defaults to `127.0.0.1/32` & `::1/128`.
+    pub ip_allowlist: Vec<IpNet>,
+    /// The maximum amount of value changes kept in the log shown at `/log`, defaults to 1000.
+    pub change_log_size: usize,
+    /// Whether to apply the environment variable overrides with `apply_env_overrides` when the
+    /// web server starts, defaults to `true`.
+    pub apply_env: bool,
+    /// The amount of significant digits shown in the number inputs next to the `f64` & `f32`
+    /// sliders, from 1 to 100, defaults to 6.
+    pub significant_digits: usize,
+    /// How long a slider has to stop moving in milliseconds before the value is sent while it's
+    /// dragged, the value is always sent when it's released, defaults to 50.
+    pub debounce_ms: u16,
+    /// The Unix domain socket to listen on instead of the `host` & `port`, defaults to `None`.
+    ///
+    /// Browsers can't connect to Unix sockets, so the web interface can only be reached through a
+    /// proxy or tools like `curl --unix-socket <path> http://localhost/`. The `ip_allowlist`
+    /// doesn't apply to the socket, access is controlled by the permissions of the socket file.
+    #[cfg(unix)]
+    pub unix_socket: Option<PathBuf>,
+}
+
+impl TweakerConfig {
+    /// Create a builder starting from the default configuration.
+    pub fn builder() -> TweakerConfigBuilder {
+        TweakerConfigBuilder::default()
+    }
+}
+
+impl Default for TweakerConfig {
+    fn default() -> Self {
+        Self {
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 9938,
+            auth_token: None,
+            max_body_size: 1024 * 1024,
+            shutdown_timeout: Duration::from_secs(5),
+            allow_origin: "*".to_string(),
+            ip_allowlist: vec![
+                IpNet::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 32).expect("Invalid prefix length"),
+                IpNet::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 128).expect("Invalid prefix length"),
+            ],
+            change_log_size: 1000,
+            apply_env: true,
+            significant_digits: 6,
+            debounce_ms: 50,
+            #[cfg(unix)]
+            unix_socket: None,
+        }
+    }
+}
+
+/// A range of IP addresses, for example `192.168.1.0/24`.
+///
+/// ```rust
+/// use const_tweaker::IpNet;
+///
+/// let net: IpNet = "192.168.1.0/24".parse()?;
+/// assert!(net.contains("192.168.1.42".parse()?));
+/// assert!(!net.contains("192.168.2.1".parse()?));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNet {
+    /// Create a range from an address and the amount of leading bits that have to match.
+    ///
+    /// Returns `None` when the prefix length is longer than the address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Self> {
+        let max_prefix_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+
+        if prefix_len <= max_prefix_len {
+            Some(Self { addr, prefix_len })
+        } else {
+            None
+        }
+    }
+
+    /// Whether the address is in the range.
+    ///
+    /// IPv4 addresses mapped to IPv6, like `::ffff:127.0.0.1`, are treated as IPv4 addresses.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let addr = match addr {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(addr),
+            addr => addr,
+        };
+
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = IpNetParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = s
+            .split_once('/')
+            .ok_or_else(|| IpNetParseError(format!("missing prefix length in {}", s)))?;
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|err| IpNetParseError(format!("invalid address {}: {}", addr, err)))?;
+        let prefix_len: u8 = prefix_len.parse().map_err(|err| {
+            IpNetParseError(format!("invalid prefix length {}: {}", prefix_len, err))
+        })?;
+
+        IpNet::new(addr, prefix_len)
+            .ok_or_else(|| IpNetParseError(format!("prefix length {} is too long", prefix_len)))
+    }
+}
+
+impl Display for IpNet {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Error parsing an [`IpNet`](struct.IpNet.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpNetParseError(String);
+
+impl Display for IpNetParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Invalid IP range: {}", self.0)
+    }
+}
+
+impl Error for IpNetParseError {}
+
+/// Builder for the [`TweakerConfig`](struct.TweakerConfig.html).
+///
+/// ```rust
+/// use const_tweaker::TweakerConfig;
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// let config = TweakerConfig::builder()
+///     .host(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+///     .port(8080)
+///     .auth_token(Some("secret".to_string()))
+///     .max_body_size(4096)
+///     .build();
+/// assert_eq!(config.auth_token.as_deref(), Some("secret"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TweakerConfigBuilder {
+    config: TweakerConfig,
+}
+
+impl TweakerConfigBuilder {
+    /// Set the address the web server binds to.
+    pub fn host(mut self, host: IpAddr) -> Self {
+        self.config.host = host;
+        self
+    }
+
+    /// Set the port the web server listens on.
+    pub fn port(mut self, port: u16) -> Self {
+        self.config.port = port;
+        self
+    }
+
+    /// Set the token that's required to access the web server.
+    pub fn auth_token(mut self, auth_token: Option<String>) -> Self {
+        self.config.auth_token = auth_token;
+        self
+    }
+
+    /// Set the maximum size in bytes of a request body.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.config.max_body_size = max_body_size;
+        self
+    }
+
+    /// Set how long to wait for requests that are still being handled when shutting down.
+    pub fn shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.config.shutdown_timeout = shutdown_timeout;
+        self
+    }
+
+    /// Set the origin that's allowed to access the web server from another site.
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        self.config.allow_origin = origin.to_string();
+        self
+    }
+
+    /// Set the addresses that are allowed to connect.
+    pub fn ip_allowlist(mut self, ip_allowlist: Vec<IpNet>) -> Self {
+        self.config.ip_allowlist = ip_allowlist;
+        self
+    }
+
+    /// Allow all addresses to connect, only use this on a network you trust.
+    pub fn allow_any_ip(mut self) -> Self {
+        self.config.ip_allowlist = vec![
+            IpNet::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0).expect("Invalid prefix length"),
+            IpNet::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0).expect("Invalid prefix length"),
+        ];
+        self
+    }
+
+    /// Set the maximum amount of value changes kept in the log.
+    pub fn change_log_size(mut self, change_log_size: usize) -> Self {
+        self.config.change_log_size = change_log_size;
+        self
+    }
+
+    /// Set whether to apply the environment variable overrides when the web server starts.
+    pub fn apply_env(mut self, apply_env: bool) -> Self {
+        self.config.apply_env = apply_env;
+        self
+    }
+
+    /// Set the amount of significant digits shown in the number inputs of floats.
+    pub fn significant_digits(mut self, significant_digits: usize) -> Self {
+        self.config.significant_digits = significant_digits;
+        self
+    }
+
+    /// Set how long a slider has to stop moving before the value is sent while it's dragged.
+    pub fn debounce_ms(mut self, debounce_ms: u16) -> Self {
+        self.config.debounce_ms = debounce_ms;
+        self
+    }
+
+    /// Listen on a Unix domain socket instead of the `host` & `port`.
+    #[cfg(unix)]
+    pub fn unix_socket(mut self, path: impl AsRef<Path>) -> Self {
+        self.config.unix_socket = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Create the configuration.
+    pub fn build(self) -> TweakerConfig {
+        self.config
+    }
+}
+
+/// Middleware adding the CORS headers to all responses & answering preflight requests.
+struct Cors {
+    allow_origin: String,
+}
+
+impl Cors {
+    /// Add the CORS headers to a response.
+    fn add_headers(&self, response: Response) -> Response {
+        response
+            .set_header("access-control-allow-origin", &self.allow_origin)
+            .set_header("access-control-allow-methods", "GET, POST, DELETE, OPTIONS")
+            .set_header(
+                "access-control-allow-headers",
+                "Authorization, Content-Type",
+            )
+    }
+}
+
+impl Middleware<()> for Cors {
+    fn handle<'a>(
+        &'a self,
+        request: Request<()>,
+        next: Next<'a, ()>,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send + 'a>> {
+        Box::pin(async move {
+            // Preflight requests don't contain the authentication token, so answer them directly
+            if request.method() == Method::OPTIONS {
+                return self.add_headers(Response::new(204));
+            }
+
+            let response = next.run(request).await;
+            self.add_headers(response)
+        })
+    }
+}
+
+/// The query string containing the optional authentication token.
+#[derive(Debug, Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Middleware rejecting requests that don't match the configuration.
+struct RequestGuard {
+    auth_token: Option<String>,
+    max_body_size: usize,
+}
+
+impl Middleware<()> for RequestGuard {
+    fn handle<'a>(
+        &'a self,
+        request: Request<()>,
+        next: Next<'a, ()>,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send + 'a>> {
+        Box::pin(async move {
+            // Health checks of container orchestrators don't know the token
+            let is_probe = matches!(request.uri().path(), "/health" | "/ready");
+            if let (Some(auth_token), false) = (&self.auth_token, is_probe) {
+                // Browsers can't send headers when opening the page or listening to events, so
+                // the token can also be passed in the query string
+                let authorized = request
+                    .header("authorization")
+                    .map(|header| header == format!("Bearer {}", auth_token))
+                    .unwrap_or(false)
+                    || request
+                        .query::<TokenQuery>()
+                        .ok()
+                        .and_then(|query| query.token)
+                        .map(|token| &token == auth_token)
+                        .unwrap_or(false);
+                if !authorized {
+                    return Response::new(401).body_string("Invalid or missing token".to_string());
+                }
+            }
+
+            // Requests without a body, like the GET request for the page, have no content length.
+            // The body is read without a limit, so bodies of which the size isn't known up front,
+            // like chunked ones, are refused.
+            let body_size = match (
+                request.header("content-length"),
+                request.header("transfer-encoding"),
+            ) {
+                (Some(length), _) => match length.trim().parse::<usize>() {
+                    Ok(body_size) => body_size,
+                    Err(_) => {
+                        return Response::new(400)
+                            .body_string(format!("Invalid content length {}", length))
+                    }
+                },
+                (None, Some(_)) => {
+                    return Response::new(411)
+                        .body_string("Request bodies require a content length".to_string())
+                }
+                (None, None) => 0,
+            };
+            if body_size > self.max_body_size {
+                return Response::new(413).body_string(format!(
+                    "Request body is {} bytes, the maximum size is {} bytes",
+                    body_size, self.max_body_size
+                ));
+            }
+
+            next.run(request).await
+        })
+    }
+}
+
+/// Whether a web server is running in this process.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// The amount of significant digits of the floats shown on the page, from the configuration.
+static SIGNIFICANT_DIGITS: AtomicUsize = AtomicUsize::new(6);
+
+/// The delay before sending the value of a slider that's dragged, from the configuration.
+static DEBOUNCE_MS: AtomicU16 = AtomicU16::new(50);
+
+/// Whether the web server is serving requests, reported by `/ready`.
+static READY: AtomicBool = AtomicBool::new(false);
+
+/// Handle to the running `const` tweaker web service.
+///
+/// Dropping the handle keeps the web server running.
+///
+/// ```rust
+/// use const_tweaker::TweakerConfig;
+///
+/// // Let the operating system pick a free port
+/// let tweaker = const_tweaker::run_with_config(TweakerConfig::builder().port(0).build())?;
+/// assert_ne!(tweaker.port(), 0);
+/// assert_eq!(tweaker.url(), format!("http://127.0.0.1:{}", tweaker.port()));
+///
+/// tweaker.shutdown()?;
+/// # Ok::<(), const_tweaker::TweakerError>(())
+/// ```
+#[must_use = "the handle is needed to know the address of the web server or to shut it down"]
+#[derive(Debug)]
+pub struct TweakerHandle {
+    addr: SocketAddr,
+    #[cfg(unix)]
+    unix_socket: Option<PathBuf>,
+    shutdown: oneshot::Sender<()>,
+    runner: Runner,
+}
+
+/// Where the web server is running, the result is an error when it panicked.
+#[derive(Debug)]
+enum Runner {
+    /// A thread started by `run`.
+    Thread(JoinHandle<()>),
+    /// A task started by `run_async`.
+    Task(task::JoinHandle<thread::Result<()>>),
+}
+
+impl TweakerHandle {
+    /// The address the web server is listening on.
+    ///
+    /// When it's listening on a Unix socket this is the unspecified address with port 0.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The port the web server is listening on.
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// The URL of the web interface, for example `http://127.0.0.1:9938`.
+    ///
+    /// IPv6 addresses are put between brackets, for example `http://[::1]:9938`. Unix sockets use
+    /// the percent-encoded path, for example `http+unix://%2Ftmp%2Ftweaker.sock/`.
+    pub fn url(&self) -> String {
+        #[cfg(unix)]
+        if let Some(path) = &self.unix_socket {
+            return format!(
+                "http+unix://{}/",
+                utf8_percent_encode(&path.to_string_lossy(), PATH_SEGMENT_ENCODE_SET)
+            );
+        }
+
+        format!("http://{}", self.addr)
+    }
+
+    /// The path of the Unix socket the web server is listening on, `None` when it's listening on
+    /// a TCP address.
+    #[cfg(unix)]
+    pub fn unix_socket(&self) -> Option<&Path> {
+        self.unix_socket.as_deref()
+    }
+
+    /// Stop the web server and wait for it to finish.
+    ///
+    /// New connections are refused immediately, requests that are still being handled get the
+    /// configured shutdown timeout to finish.
+    ///
+    /// This blocks the current thread, use `shutdown_async` from async code.
+    pub fn shutdown(self) -> Result<(), TweakerError> {
+        // The server might already have stopped because of an error, which is reported by the join
+        let _ = self.shutdown.send(());
+        match self.runner {
+            Runner::Thread(thread) => thread.join(),
+            Runner::Task(handle) => task::block_on(handle),
+        }
+        .map_err(|_| TweakerError::RuntimeError("Running web server failed".to_string()))
+    }
+
+    /// Stop the web server and wait for it to finish without blocking the current thread.
+    pub async fn shutdown_async(self) -> Result<(), TweakerError> {
+        let _ = self.shutdown.send(());
+        match self.runner {
+            Runner::Thread(thread) => {
+                // Joining the thread blocks, so wait for it on a blocking task
+                task::spawn_blocking(move || thread.join()).await
+            }
+            Runner::Task(handle) => handle.await,
+        }
+        .map_err(|_| TweakerError::RuntimeError("Running web server failed".to_string()))
+    }
+}
+
+/// Launch the `const` tweaker web service.
+///
+/// This will launch a web server at `http://127.0.01:9938`.
+///
+/// Only a single web server can run at the same time, when it's already running
+/// `TweakerError::AlreadyRunning` is returned.
+pub fn run() -> Result<TweakerHandle, TweakerError> {
+    run_with_config(TweakerConfig::default())
+}
+
+/// Launch the `const` tweaker web service as a task on the async-std executor.
+///
+/// This is the same as `run` but doesn't start a new thread, which is useful when the program
+/// already runs an async executor.
+///
+/// ```rust,no_run
+/// # async_std::task::block_on(async {
+/// let tweaker = const_tweaker::run_async().await?;
+/// println!("Tweak the values at {}", tweaker.url());
+///
+/// tweaker.shutdown_async().await?;
+/// # Ok::<(), const_tweaker::TweakerError>(())
+/// # });
+/// ```
+pub async fn run_async() -> Result<TweakerHandle, TweakerError> {
+    run_async_with_config(TweakerConfig::default()).await
+}
+
+/// Launch the `const` tweaker web service as a task with a custom configuration.
+pub async fn run_async_with_config(config: TweakerConfig) -> Result<TweakerHandle, TweakerError> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(TweakerError::AlreadyRunning);
+    }
+
+    let (listener, addr) = bind(&config).inspect_err(|_| RUNNING.store(false, Ordering::SeqCst))?;
+    #[cfg(unix)]
+    let unix_socket = config.unix_socket.clone();
+    let (shutdown, shutdown_receiver) = oneshot::channel();
+    let handle = task::spawn(
+        AssertUnwindSafe(run_server(config, listener, shutdown_receiver)).catch_unwind(),
+    );
+
+    Ok(TweakerHandle {
+        addr,
+        #[cfg(unix)]
+        unix_socket,
+        shutdown,
+        runner: Runner::Task(handle),
+    })
+}
+
+/// Launch the `const` tweaker web service on a free port picked by the operating system.
+///
+/// The port can be retrieved from the handle, this is useful when multiple programs using this
+/// library run at the same time.
+///
+/// ```rust
+/// let tweaker = const_tweaker::run_on_random_port()?;
+/// println!("Tweak the values at {}", tweaker.url());
+/// # tweaker.shutdown()?;
+/// # Ok::<(), const_tweaker::TweakerError>(())
+/// ```
+pub fn run_on_random_port() -> Result<TweakerHandle, TweakerError> {
+    run_with_config(TweakerConfig::builder().port(0).build())
+}
+
+/// Launch the `const` tweaker web service on the first free port in a range.
+///
+/// The ports from `start` up to and including `end` are tried in order.
+///
+/// ```rust,no_run
+/// // Allow multiple instances of a game to run side-by-side
+/// let tweaker = const_tweaker::run_with_port_fallback(9938, 9950)?;
+/// println!("Tweak the values at {}", tweaker.url());
+/// # Ok::<(), const_tweaker::TweakerError>(())
+/// ```
+pub fn run_with_port_fallback(start: u16, end: u16) -> Result<TweakerHandle, TweakerError> {
+    for port in start..=end {
+        match run_with_config(TweakerConfig::builder().port(port).build()) {
+            Err(TweakerError::BindFailed(err)) => {
+                log::debug!("Could not bind web server to port {}: {}", port, err)
+            }
+            result => return result,
+        }
+    }
+
+    Err(TweakerError::NoPortAvailable { start, end })
+}
+
+/// Launch the `const` tweaker web service with a custom configuration.
+///
+/// ```rust,no_run
+/// use const_tweaker::TweakerConfig;
+///
+/// // Launch the web server at `http://127.0.0.1:8080`
+/// let tweaker = const_tweaker::run_with_config(TweakerConfig::builder().port(8080).build())?;
+/// println!("Tweak the values at {}", tweaker.url());
+/// # Ok::<(), const_tweaker::TweakerError>(())
+/// ```
+pub fn run_with_config(config: TweakerConfig) -> Result<TweakerHandle, TweakerError> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(TweakerError::AlreadyRunning);
+    }
+
+    start_server(config).inspect_err(|_| RUNNING.store(false, Ordering::SeqCst))
+}
+
+/// Bind the web server & run it in a new thread.
+fn start_server(config: TweakerConfig) -> Result<TweakerHandle, TweakerError> {
+    let (listener, addr) = bind(&config)?;
+    #[cfg(unix)]
+    let unix_socket = config.unix_socket.clone();
+    let (shutdown, shutdown_receiver) = oneshot::channel();
+
+    // Run a blocking web server in a new thread
+    let thread = thread::Builder::new()
+        .name("const-tweaker".to_string())
+        .spawn(move || task::block_on(run_server(config, listener, shutdown_receiver)))
+        .map_err(|err| TweakerError::RuntimeError(err.to_string()))?;
+
+    Ok(TweakerHandle {
+        addr,
+        #[cfg(unix)]
+        unix_socket,
+        shutdown,
+        runner: Runner::Thread(thread),
+    })
+}
+
+/// The listener the web server accepts connections on.
+enum Listener {
+    Tcp(std::net::TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+/// Bind the listener & apply the configuration of the values.
+///
+/// This is done before the server is started so binding errors can be returned and the port is
+/// known.
+fn bind(config: &TweakerConfig) -> Result<(Listener, SocketAddr), TweakerError> {
+    #[cfg(unix)]
+    let bound = match &config.unix_socket {
+        Some(path) => bind_unix_socket(path).map(|listener| {
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0);
+            (Listener::Unix(listener), addr)
+        }),
+        None => bind_tcp(config),
+    };
+    #[cfg(not(unix))]
+    let bound = bind_tcp(config);
+    let (listener, addr) = bound?;
+
+    CHANGE_LOG_SIZE.store(config.change_log_size, Ordering::SeqCst);
+    // This is the range supported by `toPrecision` in the script
+    SIGNIFICANT_DIGITS.store(config.significant_digits.clamp(1, 100), Ordering::SeqCst);
+    DEBOUNCE_MS.store(config.debounce_ms, Ordering::SeqCst);
+    if config.apply_env {
+        apply_env_overrides()?;
+    }
+
+    Ok((listener, addr))
+}
+
+/// Bind the TCP address of the configuration.
+fn bind_tcp(config: &TweakerConfig) -> Result<(Listener, SocketAddr), TweakerError> {
+    let listener = std::net::TcpListener::bind(SocketAddr::new(config.host, config.port))
+        .map_err(TweakerError::BindFailed)?;
+    let addr = listener.local_addr().map_err(TweakerError::BindFailed)?;
+
+    Ok((Listener::Tcp(listener), addr))
+}
+
+/// Bind a Unix socket, a socket file left behind by a server that's no longer running is replaced.
+#[cfg(unix)]
+fn bind_unix_socket(path: &Path) -> Result<std::os::unix::net::UnixListener, TweakerError> {
+    use std::os::unix::{fs::FileTypeExt, net::UnixStream};
+
+    let is_socket = fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_socket())
+        .unwrap_or(false);
+    if is_socket && UnixStream::connect(path).is_err() {
+        fs::remove_file(path).map_err(TweakerError::BindFailed)?;
+    }
+
+    std::os::unix::net::UnixListener::bind(path).map_err(TweakerError::BindFailed)
+}
+
+/// Serve the app until the shutdown signal is received.
+async fn run_server(config: TweakerConfig, listener: Listener, shutdown: oneshot::Receiver<()>) {
+    let app = create_app(&config);
+    #[cfg(unix)]
+    let unix_socket = config.unix_socket.clone();
+    let result = serve(app, listener, shutdown, config).await;
+
+    // The socket file isn't removed when the listener is closed
+    #[cfg(unix)]
+    if let Some(path) = unix_socket {
+        let _ = fs::remove_file(path);
+    }
+
+    // Allow a new web server to be started after this one stopped
+    RUNNING.store(false, Ordering::SeqCst);
+    result.expect("Running web server failed");
+}
+
+/// Create the web server with all routes.
+fn create_app(config: &TweakerConfig) -> Server<()> {
+    let mut app = tide::new();
+    app.middleware(Cors {
+        allow_origin: config.allow_origin.clone(),
+    });
+    app.middleware(RequestGuard {
+        auth_token: config.auth_token.clone(),
+        max_body_size: config.max_body_size,
+    });
+    app.at("/").get(main_site);
+    app.at("/health").get(handle_health);
+    app.at("/ready").get(handle_ready);
+    app.at("/set/f64").post(handle_set_f64);
+    app.at("/set/f32").post(handle_set_f32);
+    app.at("/set/i32").post(handle_set_i32);
+    app.at("/set/u32").post(handle_set_u32);
+    app.at("/set/usize").post(handle_set_usize);
+    app.at("/set/u8").post(handle_set_u8);
+    app.at("/set/u16").post(handle_set_u16);
+    app.at("/set/i8").post(handle_set_i8);
+    app.at("/set/i16").post(handle_set_i16);
+    app.at("/set/i64").post(handle_set_i64);
+    app.at("/set/u128").post(handle_set_u128);
+    app.at("/set/i128").post(handle_set_i128);
+    app.at("/set/string").post(handle_set_string);
+    app.at("/set/bool").post(handle_set_bool);
+    app.at("/set/enum").post(handle_set_enum);
+    app.at("/set/color").post(handle_set_color);
+    app.at("/set/optional_f64").post(handle_set_optional_f64);
+    app.at("/set/optional_bool").post(handle_set_optional_bool);
+    app.at("/set/vec_f64").post(handle_set_vec_f64);
+    app.at("/api/delta/f64").post(handle_delta_f64);
+    app.at("/api/toggle/bool").post(handle_toggle_bool);
+    app.at("/events").get(handle_events);
+    app.at("/api/values")
+        .get(handle_get_values)
+        .post(handle_set_values);
+    app.at("/api/batch").post(handle_batch);
+    app.at("/api/reset").post(handle_reset);
+    app.at("/api/reset/:key").post(handle_reset_value);
+    app.at("/api/lock/:key")
+        .post(handle_lock)
+        .delete(handle_unlock);
+    app.at("/api/schema").get(handle_get_schema);
+    app.at("/api/list").get(handle_get_list);
+    app.at("/api/diff").get(handle_get_diff);
+    app.at("/api/defaults").get(handle_get_defaults);
+    app.at("/api/groups").get(handle_get_groups);
+    app.at("/api/snapshot")
+        .get(handle_get_snapshot)
+        .post(handle_restore_snapshot);
+    app.at("/api/load-state").get(handle_load_state);
+    app.at("/api/export.csv").get(handle_export_csv);
+    app.at("/api/presets").get(handle_get_presets);
+    app.at("/api/presets/:name")
+        .get(handle_load_preset)
+        .post(handle_save_preset)
+        .delete(handle_delete_preset);
+    app.at("/log").get(handle_log);
+    app.at("/openapi.json").get(handle_openapi);
+    app.at("/api/docs").get(handle_api_docs);
+    #[cfg(feature = "metrics")]
+    app.at("/metrics").get(handle_metrics);
+
+    app
+}
+
+/// Spawns the tasks handling the connections on the async-std executor.
+#[derive(Clone, Default)]
+struct Spawner {
+    /// The amount of connections that are still open.
+    connections: Arc<AtomicUsize>,
+}
+
+impl Spawn for &Spawner {
+    fn spawn_obj(&self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        let connections = self.connections.clone();
+        connections.fetch_add(1, Ordering::SeqCst);
+        task::spawn(async move {
+            future.await;
+            connections.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        Ok(())
+    }
+}
+
+/// A connection of which the address of the client is known to the handlers through `peer_ip`.
+struct PeerStream<S> {
+    stream: S,
+    peer_ip: Option<IpAddr>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for PeerStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        // The task reading the requests of the connection also runs the handlers
+        let peer_ip = self.peer_ip;
+        let _ = PEER_IP.try_with(|ip| ip.set(peer_ip));
+
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for PeerStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+/// The address of the client of the request that's being handled, `None` outside of a request
+/// and for connections on a Unix socket.
+pub(crate) fn peer_ip() -> Option<IpAddr> {
+    PEER_IP.try_with(|ip| ip.get()).ok().flatten()
+}
+
+/// Serve the app on the listener until the shutdown signal is received.
+///
+/// After the signal the open connections get the shutdown timeout to finish.
+async fn serve(
+    app: Server<()>,
+    listener: Listener,
+    shutdown: oneshot::Receiver<()>,
+    config: TweakerConfig,
+) -> io::Result<()> {
+    // Dropping the handle cancels the channel, which shouldn't stop the server
+    let shutdown = Box::pin(async {
+        if shutdown.await.is_err() {
+            future::pending::<()>().await
+        }
+    });
+
+    // The server stops accepting connections when the stream of incoming connections ends
+    match listener {
+        Listener::Tcp(listener) => {
+            let listener = TcpListener::from(listener);
+            let ip_allowlist = config.ip_allowlist;
+            let incoming = listener
+                .incoming()
+                .take_until(shutdown)
+                .filter_map(move |stream| {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(err) => return future::ready(Some(Err(err))),
+                    };
+
+                    let peer_ip = stream.peer_addr().ok().map(|addr| addr.ip());
+                    let allowed =
+                        peer_ip.is_some_and(|ip| ip_allowlist.iter().any(|net| net.contains(ip)));
+                    if allowed {
+                        future::ready(Some(Ok(PeerStream { stream, peer_ip })))
+                    } else {
+                        task::spawn(reject_connection(stream));
+                        future::ready(None)
+                    }
+                });
+
+            serve_incoming(app, incoming, config.shutdown_timeout).await
+        }
+        #[cfg(unix)]
+        Listener::Unix(listener) => {
+            // Access is controlled by the permissions of the socket file instead of the allowlist
+            let listener = UnixListener::from(listener);
+            let incoming = listener.incoming().take_until(shutdown);
+
+            serve_incoming(app, incoming, config.shutdown_timeout).await
+        }
+    }
+}
+
+/// Serve the app on the connections until the stream ends, after which the open connections get
+/// the shutdown timeout to finish.
+async fn serve_incoming<I, S>(
+    app: Server<()>,
+    incoming: I,
+    shutdown_timeout: Duration,
+) -> io::Result<()>
+where
+    I: Stream<Item = io::Result<S>> + Unpin,
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let spawner = Spawner::default();
+    READY.store(true, Ordering::SeqCst);
+    let result = http_service_hyper::Server::builder(incoming)
+        .with_spawner(spawner.clone())
+        .serve(app.into_http_service())
+        .await;
+
+    // Requests on connections that are still open are answered while draining, but the server
+    // isn't accepting new ones anymore
+    READY.store(false, Ordering::SeqCst);
+    result.map_err(io::Error::other)?;
+
+    // End the event streams, they would otherwise keep their connections open forever
+    EVENT_CLIENTS
+        .lock()
+        .expect("Event clients lock is poisoned")
+        .clear();
+
+    // Wait for the remaining requests, connections that are still open after the timeout are
+    // left to the executor
+    let drained = async {
+        while spawner.connections.load(Ordering::SeqCst) > 0 {
+            task::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    let _ = async_std::future::timeout(shutdown_timeout, drained).await;
+
+    Ok(())
+}
+
+/// Respond to a connection from an address that's not in the allowlist with a 403 & close it.
+async fn reject_connection(mut stream: TcpStream) {
+    // Read the request first, closing a connection with unread data resets it, which would
+    // prevent the client from reading the response
+    let mut buffer = [0; 1024];
+    let _ = async_std::future::timeout(Duration::from_secs(1), stream.read(&mut buffer)).await;
+
+    let _ = stream
+        .write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+        .await;
+    let _ = stream.shutdown(Shutdown::Write);
+}
+
+/// Send a server-sent event with the new value to all connected clients.
+pub(crate) fn broadcast_event(key: &str, field: &Field) {
+    let message = json!({
+        "key": key,
+        "type": field.type_name(),
+        "value": field.value_to_json(),
+        "changes": change_count(key),
+    });
+    let event = format!("data: {}\n\n", message);
+
+    // Remove the clients that disconnected, the channels are unbounded so sending can only fail
+    // when the receiver is dropped
+    EVENT_CLIENTS
+        .lock()
+        .expect("Event clients lock is poisoned")
+        .retain(|client| client.try_send(event.clone()).is_ok());
+}
+
+/// Build the actual site.
+async fn main_site(request: Request<()>) -> Response {
+    let body = html! {
+        style { : include_str!("bulma.css") }
+        style { : "* { font-family: sans-serif}" }
+        style { : include_str!("dark.css") }
+        style {
+            : ".tweak.modified { border-left: 4px solid #ffb000; }";
+            : ".changes-only .tweak:not(.modified) { display: none; }"
+        }
+        div (class="container") {
+            // Apply the theme before the rest of the page is rendered to prevent a flash
+            script { : Raw("if (localStorage.getItem('theme') === 'dark') document.body.classList.add('dark');") }
+            h1 (class="title") { : "Const Tweaker Web Interface" }
+            div (class="buttons") {
+                button (id="theme_toggle",
+                    class="button is-small",
+                    role="switch",
+                    aria-checked="false",
+                    title="Dark mode",
+                    onclick="toggle_theme()")
+                { : "\u{263E}" }
+                button (class="button is-small", onclick="reset_all()") { : "Reset all" }
+                button (class="button is-small", onclick="set_groups_open(false)")
+                { : "Collapse All" }
+                button (class="button is-small", onclick="set_groups_open(true)")
+                { : "Expand All" }
+                button (class="button is-small", onclick="copy_json()") { : "Copy JSON" }
+                button (class="button is-small", onclick="paste_json()") { : "Paste JSON" }
+                button (class="button is-small", onclick="share_url()") { : "Share URL" }
+                label (class="checkbox") {
+                    input (type="checkbox", id="changes_only", onchange="toggle_changes_only()") { }
+                    : " Show Changes Only"
+                }
+                span (id="undo_badge", class="tag", title="Undo with Ctrl+Z, redo with Ctrl+Shift+Z")
+                { : "Undo (0)" }
+            }
+            div (id="toast",
+                class="notification",
+                style="display: none; position: fixed; bottom: 1em; right: 1em; z-index: 1")
+            { }
+            div (class="field has-addons") {
+                div (class="control is-expanded") {
+                    input (type="text",
+                        id="search",
+                        class="input is-small",
+                        placeholder="Filter tweaks...",
+                        oninput="filter_tweaks()")
+                    { }
+                }
+                div (class="control") {
+                    button (class="button is-small", title="Clear filter", onclick="clear_filter()")
+                    { : "\u{00D7}" }
+                }
+                div (class="control") {
+                    div (class="select is-small") {
+                        select (id="sort", onchange="sort_tweaks()") {
+                            option (value="name") { : "Sort by name (A\u{2013}Z)" }
+                            option (value="name_desc") { : "Sort by name (Z\u{2013}A)" }
+                            option (value="type") { : "Sort by type" }
+                            option (value="group") { : "Sort by group" }
+                            option (value="modified") { : "Sort by recently modified" }
+                        }
+                    }
+                }
+            }
+            p { : widgets() }
+            div (class="notification is-danger") {
+                span(id="status") { }
+            }
+            details (class="box") {
+                summary { : "Presets" }
+                div (class="field has-addons") {
+                    div (class="control") {
+                        input (class="input is-small", type="text", id="preset_name", placeholder="Preset name") { }
+                    }
+                    div (class="control") {
+                        button (class="button is-small", onclick="save_preset()") { : "Save" }
+                    }
+                }
+                table (class="table is-narrow is-fullwidth") {
+                    tbody (id="presets") { }
+                }
+            }
+            details (id="log_panel", class="box") {
+                summary { : "Recent Changes" }
+                table (class="table is-narrow is-fullwidth") {
+                    thead {
+                        tr {
+                            th { : "Time" }
+                            th { : "Key" }
+                            th { : "Old value" }
+                            th { : "New value" }
+                            th { : "Source" }
+                        }
+                    }
+                    tbody (id="log") { }
+                }
+            }
+        }
+        script {
+            : Raw(format!(
+                "var significant_digits = {}; var debounce_ms = {};",
+                SIGNIFICANT_DIGITS.load(Ordering::SeqCst),
+                DEBOUNCE_MS.load(Ordering::SeqCst)
+            ))
+        }
+        script { : Raw(include_str!("send.js")) }
+    };
+
+    page_response(&request, format!("{}", body))
+}
+
+/// Respond with the page, or with a 304 when the browser already has the same page.
+///
+/// With the `compression` feature the page is gzip compressed for browsers that accept it.
+fn page_response(request: &Request<()>, page: String) -> Response {
+    let mut hasher = DefaultHasher::new();
+    page.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    #[cfg(feature = "compression")]
+    let gzip = request.header("accept-encoding").is_some_and(|encodings| {
+        encodings
+            .split(',')
+            .any(|encoding| encoding.split(';').next().unwrap_or_default().trim() == "gzip")
+    });
+    #[cfg(not(feature = "compression"))]
+    let gzip = false;
+
+    // Both encodings of the same page are different representations that need their own tag
+    let etag = format!("\"{:016x}{}\"", hash, if gzip { "-gzip" } else { "" });
+    let cached = request.header("if-none-match").is_some_and(|tags| {
+        tags.split(',')
+            .any(|tag| tag.trim() == etag || tag.trim() == "*")
+    });
+    let response = match (cached, gzip) {
+        (true, _) => Response::new(304),
+        #[cfg(feature = "compression")]
+        (false, true) => Response::new(200)
+            .body(async_std::io::Cursor::new(compressed_page(hash, &page)))
+            .set_header("content-encoding", "gzip"),
+        _ => Response::new(200).body_string(page),
+    };
+
+    response
+        .set_header("content-type", "text/html;charset=utf-8")
+        .set_header("etag", etag)
+        .set_header("vary", "Accept-Encoding")
+}
+
+/// Get the gzip compressed page, only compressing it again when it changed.
+#[cfg(feature = "compression")]
+fn compressed_page(hash: u64, page: &str) -> Vec<u8> {
+    let mut cache = COMPRESSED_PAGE
+        .lock()
+        .expect("Compressed page lock is poisoned");
+    match &*cache {
+        Some((cached_hash, compressed)) if *cached_hash == hash => compressed.clone(),
+        _ => {
+            let compressed = gzip(page.as_bytes());
+            *cache = Some((hash, compressed.clone()));
+            compressed
+        }
+    }
+}
+
+/// Compress data in the gzip format.
+#[cfg(feature = "compression")]
+fn gzip(data: &[u8]) -> Vec<u8> {
+    // The header without a file name & modification time, created on an unknown OS
+    let mut gzip = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+    gzip.extend(miniz_oxide::deflate::compress_to_vec(data, 6));
+    gzip.extend(&crc32(data).to_le_bytes());
+    gzip.extend(&(data.len() as u32).to_le_bytes());
+
+    gzip
+}
+
+/// The CRC-32 checksum used by gzip.
+#[cfg(feature = "compression")]
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// Render all widgets in a collapsible section per group, sorted by key.
+fn widgets() -> impl Render {
+    // The widgets are rendered with the key, the shown name, the description, the type, whether
+    // it's modified, locked & read-only and how often it's changed
+    type Widget = (
+        &'static str,
+        &'static str,
+        Option<&'static str>,
+        &'static str,
+        bool,
+        bool,
+        bool,
+        u64,
+        String,
+    );
+
+    let mut groups: BTreeMap<&'static str, Vec<Widget>> = BTreeMap::new();
+    for ref_multi in DATA.iter().filter(|entry| !entry.hidden) {
+        groups.entry(ref_multi.group_name()).or_default().push((
+            ref_multi.key(),
+            ref_multi.label.unwrap_or(ref_multi.key()),
+            ref_multi.description,
+            ref_multi.value.type_name(),
+            ref_multi.is_modified(),
+            ref_multi.locked,
+            ref_multi.readonly,
+            ref_multi.change_count,
+            if ref_multi.readonly {
+                readonly_widget(ref_multi.key(), &ref_multi.value)
+            } else if ref_multi.log_scale {
+                ref_multi.value.to_log_html_widget(ref_multi.key())
+            } else {
+                ref_multi.value.to_html_widget(ref_multi.key())
+            },
+        ));
+    }
+    groups.values_mut().for_each(|widgets| widgets.sort());
+
+    owned_html! {
+        @for (group, widgets) in groups.iter() {
+            details (open, class="box group", data-group=group) {
+                summary (class="subtitle") { : format!("{} ({})", group, widgets.len()) }
+                @for (key, name, description, type_name, modified, locked, readonly, changes, widget) in widgets.iter() {
+                    // The text the filter searches in & the attributes used for sorting
+                    div (class=if *modified { "columns box tweak modified" } else { "columns box tweak" },
+                        data-key=key,
+                        data-type=type_name,
+                        data-locked=if *locked { "true" } else { "false" },
+                        data-search=format!("{} {} {}",
+                            if name == key { key.to_string() } else { format!("{} {}", key, name) },
+                            description.unwrap_or_default(),
+                            group).to_lowercase())
+                    {
+                        div (class="column is-narrow") {
+                            @if let Some(description) = description {
+                                span (class="tag", title=description) { : name }
+                            } else {
+                                span (class="tag") { : name }
+                            }
+                            span (class="tag is-light change-count",
+                                id=format!("{}_changes", key),
+                                title="Times changed")
+                            { : changes }
+                        }
+                        : Raw(widget);
+                        @if !*readonly {
+                            div (class="column is-narrow") {
+                                button (class="button is-small",
+                                    title="Reset to default",
+                                    onclick=format!("reset('{}')", key))
+                                { : "\u{21BA}" }
+                            }
+                            div (class="column is-narrow") {
+                                // The inputs of locked values are disabled by the script
+                                button (class="button is-small lock",
+                                    title=if *locked { "Unlock" } else { "Lock" },
+                                    onclick=format!("toggle_lock('{}')", key))
+                                { : if *locked { "\u{1F512}" } else { "\u{1F513}" } }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Stream the value changes as server-sent events.
+async fn handle_events(_: Request<()>) -> Response {
+    let (sender, receiver) = async_channel::unbounded();
+    EVENT_CLIENTS
+        .lock()
+        .expect("Event clients lock is poisoned")
+        .push(sender);
+
+    // The response body never ends, it's dropped when the client disconnects
+    let body = receiver
+        .map(|event| Ok(event.into_bytes()))
+        .into_async_read();
+
+    Response::with_reader(200, body)
+        .set_header("content-type", "text/event-stream")
+        .set_header("cache-control", "no-cache")
+}
+
+/// Show a read-only value as text, the script updates it when the value changes.
+fn readonly_widget(key: &str, field: &Field) -> String {
+    let value = match field.value_to_json() {
+        serde_json::Value::String(text) => text,
+        value => value.to_string(),
+    };
+
+    format!(
+        "{}",
+        html! {
+            div (class="column") {
+                span (id=format!("{}_label", key), class="readonly") { : &value }
+            }
+        }
+    )
+}
+
+/// Whether the value can only be changed from code, not from the web interface.
+fn is_readonly(key: &str) -> bool {
+    DATA.get(key).is_some_and(|entry| entry.readonly)
+}
+
+/// The response refusing changes to read-only values from the web interface.
+fn readonly_response(key: &str) -> Option<Response> {
+    if is_readonly(key) {
+        Some(Response::new(403).body_string(format!("Value {} is read-only", key)))
+    } else {
+        None
+    }
+}
+
+/// Apply an update to a field from a POST request and convert the result to a response.
+fn update_field(key: &str, type_name: &str, update: impl FnOnce(&mut Field)) -> Response {
+    if let Some(response) = readonly_response(key) {
+        return response;
+    }
+
+    match set_field(key, type_name, update) {
+        Ok(()) => Response::new(200),
+        Err(err) => error_response(key, err),
+    }
+}
+
+/// Convert the reason a value couldn't be changed to a response.
+fn error_response(key: &str, err: UpdateError) -> Response {
+    match err {
+        UpdateError::Missing => {
+            Response::new(404).body_string(format!("Value {} doesn't exist", key))
+        }
+        UpdateError::TypeMismatch { expected, actual } => Response::new(400)
+            .body_string(format!("Value {} is a {}, not a {}", key, actual, expected)),
+        UpdateError::InvalidValue(err) => {
+            Response::new(400).body_string(format!("Invalid value for {}: {}", key, err))
+        }
+        UpdateError::Locked => Response::new(423).body_string(format!("Value {} is locked", key)),
+        UpdateError::OutOfRange(err) => Response::new(400).body_string(err),
+    }
+}
+
+async fn handle_get_values(_: Request<()>) -> Response {
+    let values: serde_json::Map<String, serde_json::Value> = DATA
+        .iter()
+        .map(|entry| (entry.key().to_string(), entry.value().to_json()))
+        .collect();
+
+    Response::new(200)
+        .body_json(&values)
+        .expect("Could not encode JSON")
+}
+
+async fn handle_get_diff(_: Request<()>) -> Response {
+    let values: serde_json::Map<String, serde_json::Value> = DATA
+        .iter()
+        .filter(|entry| entry.is_modified())
+        .map(|entry| (entry.key().to_string(), entry.value().to_json()))
+        .collect();
+
+    Response::new(200)
+        .body_json(&values)
+        .expect("Could not encode JSON")
+}
+
+async fn handle_get_defaults(_: Request<()>) -> Response {
+    let defaults: serde_json::Map<String, serde_json::Value> = DATA
+        .iter()
+        .map(|entry| {
+            let default = json!({
+                "type": entry.default.type_name(),
+                "default": entry.default.value_to_json(),
+            });
+
+            (entry.key().to_string(), default)
+        })
+        .collect();
+
+    Response::new(200)
+        .body_json(&defaults)
+        .expect("Could not encode JSON")
+}
+
+async fn handle_set_values(mut request: Request<()>) -> Response {
+    let values: HashMap<String, serde_json::Value> = match request.body_json().await {
+        Ok(values) => values,
+        Err(err) => return invalid_json(err),
+    };
+
+    // Check all values before storing any of them, so either all or none are changed
+    let mut updates = Vec::with_capacity(values.len());
+    for (key, json) in values.iter() {
+        if let Some(response) = readonly_response(key) {
+            return response;
+        }
+        match json_update(key, json) {
+            Ok(field) => updates.push((key, field)),
+            Err(err) => return error_response(key, err),
+        }
+    }
+    store_fields(
+        updates
+            .into_iter()
+            .map(|(key, field)| (key.as_str(), field)),
+    );
+
+    Response::new(200)
+}
+
+// Handle setting of values
+/// Decode the JSON body of a request, a malformed body is responded to with a 400.
+async fn decode_body<T: DeserializeOwned>(request: &mut Request<()>) -> Result<T, Response> {
+    request.body_json().await.map_err(invalid_json)
+}
+
+/// Decode the POST data of an integer value.
+///
+/// A number outside of the range of the type is refused with the status & the expected range,
+/// other invalid bodies are refused like with `decode_body`.
+async fn decode_integer<T: TryFrom<i128>>(
+    request: &mut Request<()>,
+    type_name: &str,
+    status: u16,
+    expected: &str,
+) -> Result<(String, T), Response> {
+    let PostData { key, value } = decode_body::<PostData<serde_json::Number>>(request).await?;
+    let integer = value
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| value.as_u64().map(i128::from));
+    match integer.map(T::try_from) {
+        Some(Ok(value)) => Ok((key, value)),
+        // Integers that don't fit in 64 bits are parsed as floats
+        Some(Err(_)) | None if value.as_f64().is_some_and(|float| float.fract() == 0.0) => {
+            Err(Response::new(status).body_string(format!(
+                "Invalid {} value, expected {}: {} is out of range",
+                type_name, expected, value
+            )))
+        }
+        _ => Err(invalid_json(format!(
+            "invalid type: {}, expected {}",
+            value, type_name
+        ))),
+    }
+}
+
+/// Respond to a malformed JSON body with a 400 & the reason as JSON.
+fn invalid_json(err: impl Display) -> Response {
+    Response::new(400)
+        .body_json(&json!({ "error": format!("invalid JSON: {}", err) }))
+        .expect("Could not encode JSON")
+}
+
+async fn handle_set_f64(mut request: Request<()>) -> Response {
+    let post_data: PostData<f64> = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&post_data.key, "f64", |field| {
+        field.set_f64(post_data.value);
+    })
+}
+
+async fn handle_set_f32(mut request: Request<()>) -> Response {
+    let post_data: PostData<f32> = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&post_data.key, "f32", |field| {
+        field.set_f32(post_data.value);
+    })
+}
+
+async fn handle_set_i32(mut request: Request<()>) -> Response {
+    let post_data: PostData<i32> = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&post_data.key, "i32", |field| {
+        field.set_i32(post_data.value);
+    })
+}
+
+async fn handle_set_u32(mut request: Request<()>) -> Response {
+    let (key, value) =
+        match decode_integer::<u32>(&mut request, "u32", 400, "0 to 4294967295").await {
+            Ok(post_data) => post_data,
+            Err(response) => return response,
+        };
+    update_field(&key, "u32", |field| {
+        field.set_u32(value);
+    })
+}
+
+async fn handle_set_usize(mut request: Request<()>) -> Response {
+    // The range depends on the platform, which matters on 32-bit targets
+    let expected = format!("0 to {}", usize::MAX);
+    let (key, value) = match decode_integer::<usize>(&mut request, "usize", 400, &expected).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&key, "usize", |field| {
+        field.set_usize(value);
+    })
+}
+
+async fn handle_set_u8(mut request: Request<()>) -> Response {
+    let (key, value) = match decode_integer::<u8>(&mut request, "u8", 400, "0-255").await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&key, "u8", |field| {
+        field.set_u8(value);
+    })
+}
+
+async fn handle_set_u16(mut request: Request<()>) -> Response {
+    let (key, value) = match decode_integer::<u16>(&mut request, "u16", 400, "0 to 65535").await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&key, "u16", |field| {
+        field.set_u16(value);
+    })
+}
+
+async fn handle_set_i8(mut request: Request<()>) -> Response {
+    let (key, value) = match decode_integer::<i8>(&mut request, "i8", 422, "-128 to 127").await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&key, "i8", |field| {
+        field.set_i8(value);
+    })
+}
+
+async fn handle_set_i16(mut request: Request<()>) -> Response {
+    let (key, value) =
+        match decode_integer::<i16>(&mut request, "i16", 422, "-32768 to 32767").await {
+            Ok(post_data) => post_data,
+            Err(response) => return response,
+        };
+    update_field(&key, "i16", |field| {
+        field.set_i16(value);
+    })
+}
+
+async fn handle_set_i64(mut request: Request<()>) -> Response {
+    // The value is sent as a string because a JSON number can't hold all i64 values
+    let post_data: PostData<String> = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    let value = match post_data.value.parse::<i64>() {
+        Ok(value) => value,
+        Err(err) => return Response::new(400).body_string(format!("Invalid i64 value: {}", err)),
+    };
+    update_field(&post_data.key, "i64", |field| {
+        field.set_i64(value);
+    })
+}
+
+async fn handle_set_i128(mut request: Request<()>) -> Response {
+    let post_data: PostData<String> = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    let value = match post_data.value.parse::<i128>() {
+        Ok(value) => value,
+        Err(err) => return Response::new(400).body_string(format!("Invalid i128 value: {}", err)),
+    };
+    update_field(&post_data.key, "i128", |field| {
+        field.set_i128(value);
+    })
+}
+
+async fn handle_set_u128(mut request: Request<()>) -> Response {
+    let post_data: PostData<String> = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    let value = match post_data.value.parse::<u128>() {
+        Ok(value) => value,
+        Err(err) => return Response::new(400).body_string(format!("Invalid u128 value: {}", err)),
+    };
+    update_field(&post_data.key, "u128", |field| {
+        field.set_u128(value);
+    })
+}
+
+async fn handle_set_string(mut request: Request<()>) -> Response {
+    let PostData { key, value } = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&key, "string", |field| {
+        field.set_string(value);
+    })
+}
+
+/// Set multiple values & report the result for every value.
+///
+/// The valid values are all stored before any callbacks are called, invalid values are skipped.
+async fn handle_batch(mut request: Request<()>) -> Response {
+    let batch: Vec<BatchUpdate> = match decode_body(&mut request).await {
+        Ok(batch) => batch,
+        Err(response) => return response,
+    };
+
+    let mut updates = Vec::with_capacity(batch.len());
+    let results: Vec<serde_json::Value> = batch
+        .iter()
+        .map(|update| match batch_update(update) {
+            Ok(field) => {
+                updates.push((update.key.as_str(), field));
+                json!({ "key": update.key, "ok": true })
+            }
+            Err(err) => json!({ "key": update.key, "ok": false, "error": err }),
+        })
+        .collect();
+    store_fields(updates);
+
+    Response::new(200)
+        .body_json(&results)
+        .expect("Could not encode JSON")
+}
+
+/// Check a value of a batch update, the type has to match the registered value.
+fn batch_update(update: &BatchUpdate) -> Result<Field, String> {
+    if is_readonly(&update.key) {
+        return Err("the value is read-only".to_string());
+    }
+    let actual = DATA
+        .get(update.key.as_str())
+        .map(|entry| entry.value.type_name())
+        .ok_or_else(|| UpdateError::Missing.to_string())?;
+    if actual != update.type_name {
+        return Err(UpdateError::TypeMismatch {
+            expected: update.type_name.clone(),
+            actual,
+        }
+        .to_string());
+    }
+
+    json_update(&update.key, &json!({ "value": update.value })).map_err(|err| err.to_string())
+}
+
+async fn handle_reset(_: Request<()>) -> Response {
+    // Read-only & locked values can only be reset from the program
+    let keys: Vec<&'static str> = DATA
+        .iter()
+        .filter(|entry| !entry.readonly && !entry.locked)
+        .map(|entry| *entry.key())
+        .collect();
+    keys.into_iter().for_each(|key| {
+        reset_value(key);
+    });
+
+    Response::new(200)
+}
+
+async fn handle_reset_value(request: Request<()>) -> Response {
+    let key: String = match request.param("key") {
+        Ok(key) => key,
+        Err(_) => return Response::new(400),
+    };
+    if let Some(response) = readonly_response(&key) {
+        return response;
+    }
+    if DATA.get(key.as_str()).is_some_and(|entry| entry.locked) {
+        return error_response(&key, UpdateError::Locked);
+    }
+
+    if reset_value(&key) {
+        Response::new(200)
+    } else {
+        Response::new(404).body_string(format!("Value {} doesn't exist", key))
+    }
+}
+
+async fn handle_export_csv(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_string(export_csv())
+        .set_header("content-type", "text/csv")
+        .set_header("content-disposition", "attachment; filename=\"tweaks.csv\"")
+}
+
+async fn handle_get_presets(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_json(&presets())
+        .expect("Could not encode JSON")
+}
+
+/// The percent-decoded name of the preset in the URL.
+fn preset_name(request: &Request<()>) -> Option<String> {
+    let name: String = request.param("name").ok()?;
+    percent_encoding::percent_decode(name.as_bytes())
+        .decode_utf8()
+        .ok()
+        .map(|name| name.into_owned())
+}
+
+async fn handle_save_preset(request: Request<()>) -> Response {
+    let name = match preset_name(&request) {
+        Some(name) => name,
+        None => return Response::new(400),
+    };
+
+    Response::new(200)
+        .body_json(&save_preset(&name))
+        .expect("Could not encode JSON")
+}
+
+async fn handle_load_preset(request: Request<()>) -> Response {
+    let name = match preset_name(&request) {
+        Some(name) => name,
+        None => return Response::new(400),
+    };
+
+    // Clone the snapshot so the map isn't locked while the callbacks are called
+    let snapshot = PRESETS.get(&name).map(|snapshot| snapshot.clone());
+    match snapshot {
+        Some(snapshot) => restore_snapshot_response(snapshot),
+        None => Response::new(404).body_string(format!("Preset {} doesn't exist", name)),
+    }
+}
+
+async fn handle_delete_preset(request: Request<()>) -> Response {
+    let name = match preset_name(&request) {
+        Some(name) => name,
+        None => return Response::new(400),
+    };
+
+    if delete_preset(&name) {
+        Response::new(200)
+    } else {
+        Response::new(404).body_string(format!("Preset {} doesn't exist", name))
+    }
+}
+
+async fn handle_lock(request: Request<()>) -> Response {
+    lock_response(request, true)
+}
+
+async fn handle_unlock(request: Request<()>) -> Response {
+    lock_response(request, false)
+}
+
+/// Lock or unlock the value of the key in the URL.
+fn lock_response(request: Request<()>, locked: bool) -> Response {
+    let key: String = match request.param("key") {
+        Ok(key) => key,
+        Err(_) => return Response::new(400),
+    };
+
+    if set_locked(&key, locked) {
+        Response::new(200)
+    } else {
+        Response::new(404).body_string(format!("Value {} doesn't exist", key))
+    }
+}
+
+async fn handle_delta_f64(mut request: Request<()>) -> Response {
+    let DeltaData { key, delta } = match request.body_json().await {
+        Ok(delta_data) => delta_data,
+        Err(err) => return invalid_json(err),
+    };
+    if let Some(response) = readonly_response(&key) {
+        return response;
+    }
+
+    match alter_field(&key, "f64", |field| field.add_f64(delta)) {
+        Ok(field) => Response::new(200)
+            .body_json(&json!({ "value": field.value_to_json() }))
+            .expect("Could not encode JSON"),
+        Err(err) => error_response(&key, err),
+    }
+}
+
+async fn handle_toggle_bool(mut request: Request<()>) -> Response {
+    let KeyData { key } = match request.body_json().await {
+        Ok(key_data) => key_data,
+        Err(err) => return invalid_json(err),
+    };
+    if let Some(response) = readonly_response(&key) {
+        return response;
+    }
+
+    match alter_field(&key, "bool", Field::toggle_bool) {
+        Ok(field) => Response::new(200)
+            .body_json(&json!({ "value": field.value_to_json() }))
+            .expect("Could not encode JSON"),
+        Err(err) => error_response(&key, err),
+    }
+}
+
+/// The same JSON as `list_json`, it's left out of the OpenAPI document because it's only meant
+/// for debugging.
+async fn handle_get_list(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_string(list_json())
+        .set_header("content-type", "application/json")
+}
+
+async fn handle_get_schema(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_json(&enumerate_tweaks())
+        .expect("Could not encode JSON")
+}
+
+async fn handle_get_groups(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_json(&groups())
+        .expect("Could not encode JSON")
+}
+
+async fn handle_get_snapshot(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_json(&take_snapshot())
+        .expect("Could not encode JSON")
+}
+
+async fn handle_restore_snapshot(mut request: Request<()>) -> Response {
+    match request.body_json().await {
+        Ok(snapshot) => restore_snapshot_response(snapshot),
+        Err(err) => invalid_json(err),
+    }
+}
+
+/// The query string containing a base64 encoded snapshot.
+#[derive(Debug, Deserialize)]
+struct StateQuery {
+    state: String,
+}
+
+async fn handle_load_state(request: Request<()>) -> Response {
+    let state = match request.query::<StateQuery>() {
+        Ok(query) => query.state,
+        Err(_) => return Response::new(400).body_string("Missing the state parameter".to_string()),
+    };
+    let json = match decode_base64(&state) {
+        Ok(json) => json,
+        Err(err) => return Response::new(400).body_string(format!("Invalid base64: {}", err)),
+    };
+
+    match serde_json::from_slice(&json) {
+        Ok(snapshot) => restore_snapshot_response(snapshot),
+        Err(err) => invalid_json(err),
+    }
+}
+
+/// Restore a snapshot sent to the server, read-only values in it are skipped.
+fn restore_snapshot_response(snapshot: Snapshot) -> Response {
+    // Check all values before storing any of them, so either all or none are restored
+    let mut updates = Vec::with_capacity(snapshot.values.len());
+    for (key, json) in snapshot.values.iter() {
+        if is_readonly(key) {
+            continue;
+        }
+
+        match snapshot_update(key, json) {
+            Ok(Some(field)) => updates.push((key, field)),
+            Ok(None) => (),
+            Err(err) => return error_response(key, err),
+        }
+    }
+    store_fields(
+        updates
+            .into_iter()
+            .map(|(key, field)| (key.as_str(), field)),
+    );
+
+    Response::new(200)
+}
+
+async fn handle_set_enum(mut request: Request<()>) -> Response {
+    let PostData { key, value } = match request.body_json::<PostData<u64>>().await {
+        Ok(post_data) => post_data,
+        Err(err) => return invalid_json(err),
+    };
+    if let Some(response) = readonly_response(&key) {
+        return response;
+    }
+
+    // Enums are custom types, the value is checked by converting it to the enum
+    let actual = DATA.get(key.as_str()).map(|entry| match entry.value {
+        Field::Custom { .. } => None,
+        ref field => Some(field.type_name()),
+    });
+    if let Some(Some(actual)) = actual {
+        return error_response(
+            &key,
+            UpdateError::TypeMismatch {
+                expected: "enum".to_string(),
+                actual,
+            },
+        );
+    }
+
+    match json_update(&key, &json!({ "value": value })) {
+        Ok(field) => {
+            store_field(&key, field);
+            Response::new(200)
+        }
+        Err(err) => error_response(&key, err),
+    }
+}
+
+async fn handle_set_color(mut request: Request<()>) -> Response {
+    let PostData { key, value } = match request.body_json::<PostData<String>>().await {
+        Ok(post_data) => post_data,
+        Err(err) => return invalid_json(err),
+    };
+    if let Some(response) = readonly_response(&key) {
+        return response;
+    }
+
+    let current = match DATA.get(key.as_str()) {
+        Some(entry) => entry.value.value_to_json(),
+        None => return error_response(&key, UpdateError::Missing),
+    };
+    let ([r, g, b], alpha) = match parse_hex_color(&value) {
+        Ok(color) => color,
+        Err(err) => return error_response(&key, UpdateError::InvalidValue(err)),
+    };
+    // Keep the current alpha when it's not sent
+    let alpha = alpha.unwrap_or_else(|| current[3].as_f64().unwrap_or(1.0) as f32);
+
+    set_custom(&key, "[f32; 4]", json!([r, g, b, alpha]))
+}
+
+/// Set a value of a type implementing `Tweakable`, the type has to match.
+fn set_custom(key: &str, type_name: &str, value: serde_json::Value) -> Response {
+    match json_update(key, &json!({ "value": value })).and_then(|field| match field.type_name() {
+        actual if actual == type_name => Ok(field),
+        actual => Err(UpdateError::TypeMismatch {
+            expected: type_name.to_string(),
+            actual,
+        }),
+    }) {
+        Ok(field) => {
+            store_field(key, field);
+            Response::new(200)
+        }
+        Err(err) => error_response(key, err),
+    }
+}
+
+/// A struct used for deserializing the POST request JSON data of values that can be disabled.
+#[derive(Debug, Deserialize)]
+struct OptionalPostData<T> {
+    key: String,
+    enabled: bool,
+    value: Option<T>,
+}
+
+async fn handle_set_optional_f64(request: Request<()>) -> Response {
+    set_optional::<f64>(request, "Option<f64>").await
+}
+
+async fn handle_set_optional_bool(request: Request<()>) -> Response {
+    set_optional::<bool>(request, "Option<bool>").await
+}
+
+/// Enable a value with the value from the request or disable it.
+async fn set_optional<T: DeserializeOwned + Serialize>(
+    mut request: Request<()>,
+    type_name: &'static str,
+) -> Response {
+    let OptionalPostData {
+        key,
+        enabled,
+        value,
+    } = match request.body_json::<OptionalPostData<T>>().await {
+        Ok(post_data) => post_data,
+        Err(err) => return invalid_json(err),
+    };
+    if let Some(response) = readonly_response(&key) {
+        return response;
+    }
+
+    let value = match (enabled, value) {
+        (true, Some(value)) => json!(value),
+        (true, None) => {
+            return error_response(
+                &key,
+                UpdateError::InvalidValue("missing the value of the enabled value".to_string()),
+            )
+        }
+        (false, _) => serde_json::Value::Null,
+    };
+
+    set_custom(&key, type_name, value)
+}
+
+async fn handle_set_vec_f64(mut request: Request<()>) -> Response {
+    let PostData { key, value } = match request.body_json::<PostData<Vec<f64>>>().await {
+        Ok(post_data) => post_data,
+        Err(err) => return invalid_json(err),
+    };
+    if let Some(response) = readonly_response(&key) {
+        return response;
+    }
+
+    set_custom(&key, "Vec<f64>", json!(value))
+}
+
+/// Parse a `#RRGGBB` or `#RRGGBBAA` hex color to the channels from `0.0` to `1.0`.
+///
+/// When the alpha is missing it's `None`.
+fn parse_hex_color(hex: &str) -> Result<([f32; 3], Option<f32>), String> {
+    let digits = hex
+        .strip_prefix('#')
+        .filter(|digits| (digits.len() == 6 || digits.len() == 8) && digits.is_ascii())
+        .ok_or_else(|| format!("expected a #RRGGBB or #RRGGBBAA color, got {}", hex))?;
+    let channels = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map(|channel| channel as f32 / 255.0))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("invalid color {}: {}", hex, err))?;
+
+    Ok((
+        [channels[0], channels[1], channels[2]],
+        channels.get(3).copied(),
+    ))
+}
+
+/// Decode standard or URL-safe base64, the padding is optional.
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for character in text.trim_end_matches('=').chars() {
+        let value = match character {
+            'A'..='Z' => character as u32 - 'A' as u32,
+            'a'..='z' => character as u32 - 'a' as u32 + 26,
+            '0'..='9' => character as u32 - '0' as u32 + 52,
+            // A '+' that's not percent-encoded in the query string is decoded as a space
+            '+' | '-' | ' ' => 62,
+            '/' | '_' => 63,
+            _ => return Err(format!("unexpected character '{}'", character)),
+        };
+
+        buffer = buffer << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// The query string of the change log with the optional key to filter on.
+#[derive(Debug, Deserialize)]
+struct LogQuery {
+    key: Option<String>,
+}
+
+async fn handle_log(request: Request<()>) -> Response {
+    let key = request.query::<LogQuery>().ok().and_then(|query| query.key);
+    let entries = change_log()
+        .iter()
+        .filter(|entry| key.as_ref().is_none_or(|key| &entry.key == key))
+        .map(ChangeLogEntry::to_json)
+        .collect::<Vec<_>>();
+
+    Response::new(200)
+        .body_json(&entries)
+        .expect("Could not encode JSON")
+}
+
+async fn handle_set_bool(mut request: Request<()>) -> Response {
+    let post_data: PostData<bool> = match decode_body(&mut request).await {
+        Ok(post_data) => post_data,
+        Err(response) => return response,
+    };
+    update_field(&post_data.key, "bool", |field| {
+        field.set_bool(post_data.value);
+    })
+}
+
+/// Liveness probe, answering means the web server is alive.
+async fn handle_health(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_json(&json!({ "status": "ok" }))
+        .expect("Could not encode JSON")
+}
+
+/// Readiness probe, only succeeds while the web server accepts new connections.
+async fn handle_ready(_: Request<()>) -> Response {
+    let ready = READY.load(Ordering::SeqCst);
+    Response::new(if ready { 200 } else { 503 })
+        .body_json(&json!({ "ready": ready }))
+        .expect("Could not encode JSON")
+}
+
+/// Expose the `f64` & `bool` values and the amount of changes in the Prometheus text format.
+#[cfg(feature = "metrics")]
+async fn handle_metrics(_: Request<()>) -> Response {
+    let mut f64_values = Vec::new();
+    let mut bool_values = Vec::new();
+    for entry in DATA.iter() {
+        // The label values can contain any character except for these
+        let key = entry
+            .key()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        match entry.value {
+            Field::F64 { value, .. } => f64_values.push((key, prometheus_float(value))),
+            Field::Bool { value } => bool_values.push((key, (value as u8).to_string())),
+            _ => (),
+        }
+    }
+    f64_values.sort();
+    bool_values.sort();
+
+    let mut metrics = String::new();
+    for (name, help, values) in [
+        (
+            "const_tweaker_f64_value",
+            "The current value of a f64 tweak.",
+            f64_values,
+        ),
+        (
+            "const_tweaker_bool_value",
+            "The current value of a bool tweak, 0 or 1.",
+            bool_values,
+        ),
+    ] {
+        metrics.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for (key, value) in values {
+            metrics.push_str(&format!("{}{{key=\"{}\"}} {}\n", name, key, value));
+        }
+    }
+    metrics.push_str(&format!(
+        "# HELP const_tweaker_change_total The amount of value changes.\n\
+         # TYPE const_tweaker_change_total counter\n\
+         const_tweaker_change_total {}\n",
+        CHANGE_COUNT.load(Ordering::SeqCst)
+    ));
+
+    Response::new(200)
+        .body_string(metrics)
+        .set_header("content-type", "text/plain; version=0.0.4")
+}
+
+/// Format a float like Prometheus does, which differs from Rust for infinity.
+#[cfg(feature = "metrics")]
+fn prometheus_float(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+async fn handle_openapi(_: Request<()>) -> Response {
+    Response::new(200)
+        .body_json(&openapi())
+        .expect("Could not encode JSON")
+}
+
+/// Show the OpenAPI document with Swagger UI.
+async fn handle_api_docs(_: Request<()>) -> Response {
+    let body = html! {
+        : Raw("<!DOCTYPE html>");
+        html {
+            head {
+                title { : "const-tweaker API" }
+                link (rel="stylesheet", href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css");
+            }
+            body {
+                div (id="swagger-ui") { }
+                script (src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js") { }
+                script { : Raw("SwaggerUIBundle({url: '/openapi.json', dom_id: '#swagger-ui'});") }
+            }
+        }
+    };
+
+    Response::new(200)
+        .body_string(format!("{}", body))
+        .set_header("content-type", "text/html;charset=utf-8")
+}
+
+/// Describe all endpoints as an OpenAPI 3.0 document.
+///
+/// It's built for every request so the keys of the values registered in the meantime are listed.
+fn openapi() -> serde_json::Value {
+    let keys = get_registered_keys();
+
+    let schema = |name: &str| json!({ "$ref": format!("#/components/schemas/{}", name) });
+    let json_body = |schema: serde_json::Value| json!({ "required": true, "content": { "application/json": { "schema": schema } } });
+    let json_response = |description: &str, schema: serde_json::Value| {
+        json!({
+            "description": description,
+            "content": { "application/json": { "schema": schema } }
+        })
+    };
+    let ok = json!({ "description": "The value is changed" });
+    let errors = json!({
+        "400": { "$ref": "#/components/responses/BadRequest" },
+        "404": { "$ref": "#/components/responses/NotFound" },
+        "423": { "$ref": "#/components/responses/Locked" },
+    });
+    let with_errors = |ok: serde_json::Value| {
+        let mut responses = errors.clone();
+        responses["200"] = ok;
+        responses
+    };
+    let key_parameter = json!([{
+        "name": "key",
+        "in": "path",
+        "required": true,
+        "schema": schema("Key"),
+    }]);
+    let name_parameter = json!([{
+        "name": "name",
+        "in": "path",
+        "required": true,
+        "schema": { "type": "string" },
+    }]);
+
+    // The endpoints setting a single value of a type, with the schema of the value
+    let integer = |format: &str| json!({ "type": "integer", "format": format });
+    let setters = vec![
+        ("f64", json!({ "type": "number", "format": "double" })),
+        ("f32", json!({ "type": "number", "format": "float" })),
+        ("i32", integer("int32")),
+        ("u32", integer("uint32")),
+        ("usize", integer("uint64")),
+        ("u8", integer("uint8")),
+        ("u16", integer("uint16")),
+        ("i8", integer("int8")),
+        ("i16", integer("int16")),
+        // Large integers are sent as strings because they don't fit in a JSON number
+        ("i64", json!({ "type": "string", "pattern": "^-?[0-9]+$" })),
+        ("u128", json!({ "type": "string", "pattern": "^[0-9]+$" })),
+        ("i128", json!({ "type": "string", "pattern": "^-?[0-9]+$" })),
+        ("string", json!({ "type": "string" })),
+        ("bool", json!({ "type": "boolean" })),
+        (
+            "enum",
+            json!({ "type": "integer", "description": "The index of the variant" }),
+        ),
+        (
+            "color",
+            json!({ "type": "string", "pattern": "^#([0-9a-fA-F]{6}|[0-9a-fA-F]{8})$" }),
+        ),
+        (
+            "vec_f64",
+            json!({ "type": "array", "items": { "type": "number", "format": "double" } }),
+        ),
+    ];
+
+    let mut paths = serde_json::Map::new();
+    paths.insert(
+        "/".to_string(),
+        json!({ "get": {
+            "summary": "The web interface",
+            "responses": { "200": { "description": "The HTML page" } },
+        } }),
+    );
+    for (type_name, value) in setters {
+        paths.insert(
+            format!("/set/{}", type_name),
+            json!({ "post": {
+                "summary": format!("Set a {} value", type_name),
+                "requestBody": json_body(json!({
+                    "type": "object",
+                    "required": ["key", "value"],
+                    "properties": { "key": schema("Key"), "value": value },
+                })),
+                "responses": with_errors(ok.clone()),
+            } }),
+        );
+    }
+    // The values that can be disabled, the value is only required when it's enabled
+    let optional_setters = [
+        ("f64", json!({ "type": "number", "format": "double" })),
+        ("bool", json!({ "type": "boolean" })),
+    ];
+    for (type_name, value) in optional_setters {
+        paths.insert(
+            format!("/set/optional_{}", type_name),
+            json!({ "post": {
+                "summary": format!("Enable an Option<{}> value with the value or disable it", type_name),
+                "requestBody": json_body(json!({
+                    "type": "object",
+                    "required": ["key", "enabled"],
+                    "properties": {
+                        "key": schema("Key"),
+                        "enabled": { "type": "boolean" },
+                        "value": value,
+                    },
+                })),
+                "responses": with_errors(ok.clone()),
+            } }),
+        );
+    }
+    let other_paths = json!({
+        "/api/delta/f64": { "post": {
+            "summary": "Add a delta to a f64 value, clamped to its bounds",
+            "requestBody": json_body(json!({
+                "type": "object",
+                "required": ["key", "delta"],
+                "properties": { "key": schema("Key"), "delta": { "type": "number" } },
+            })),
+            "responses": with_errors(json_response("The new value", schema("NewValue"))),
+        } },
+        "/api/toggle/bool": { "post": {
+            "summary": "Flip a bool value",
+            "requestBody": json_body(json!({
+                "type": "object",
+                "required": ["key"],
+                "properties": { "key": schema("Key") },
+            })),
+            "responses": with_errors(json_response("The new value", schema("NewValue"))),
+        } },
+        "/events": { "get": {
+            "summary": "Stream the value changes as server-sent events",
+            "responses": { "200": {
+                "description": "An event with the key, type & value of every change",
+                "content": { "text/event-stream": { "schema": { "type": "string" } } },
+            } },
+        } },
+        "/api/values": {
+            "get": {
+                "summary": "All values with their defaults & bounds",
+                "responses": { "200": json_response("The values by key", schema("Values")) },
+            },
+            "post": {
+                "summary": "Set multiple values, either all or none are set",
+                "requestBody": json_body(schema("Values")),
+                "responses": with_errors(ok.clone()),
+            },
+        },
+        "/api/diff": { "get": {
+            "summary": "The values that differ from their defaults",
+            "responses": { "200": json_response("The values by key", schema("Values")) },
+        } },
+        "/api/defaults": { "get": {
+            "summary": "The defaults the values were registered with",
+            "responses": { "200": json_response("The defaults by key", json!({
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "properties": {
+                        "type": { "type": "string" },
+                        "default": schema("Value"),
+                    },
+                },
+            })) },
+        } },
+        "/api/batch": { "post": {
+            "summary": "Set multiple values, all valid values are set before the callbacks are called",
+            "requestBody": json_body(json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["key", "type", "value"],
+                    "properties": {
+                        "key": schema("Key"),
+                        "type": { "type": "string" },
+                        "value": schema("Value"),
+                    },
+                },
+            })),
+            "responses": {
+                "200": json_response("The result for every value", json!({
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "key": { "type": "string" },
+                            "ok": { "type": "boolean" },
+                            "error": { "type": "string" },
+                        },
+                    },
+                })),
+                "400": { "$ref": "#/components/responses/BadRequest" },
+            },
+        } },
+        "/api/reset": { "post": {
+            "summary": "Reset all values except the read-only & locked ones to their defaults",
+            "responses": { "200": { "description": "The values are reset" } },
+        } },
+        "/api/reset/{key}": { "post": {
+            "summary": "Reset a value to its default",
+            "parameters": key_parameter.clone(),
+            "responses": with_errors(ok.clone()),
+        } },
+        "/api/lock/{key}": {
+            "post": {
+                "summary": "Refuse changes to a value",
+                "parameters": key_parameter.clone(),
+                "responses": with_errors(json!({ "description": "The value is locked" })),
+            },
+            "delete": {
+                "summary": "Allow changes to a locked value",
+                "parameters": key_parameter,
+                "responses": with_errors(json!({ "description": "The value is unlocked" })),
+            },
+        },
+        "/api/schema": { "get": {
+            "summary": "Describe all values, sorted by key",
+            "responses": { "200": json_response(
+                "The descriptions",
+                json!({ "type": "array", "items": schema("TweakInfo") }),
+            ) },
+        } },
+        "/api/groups": { "get": {
+            "summary": "The names of all groups, sorted alphabetically",
+            "responses": { "200": json_response(
+                "The names",
+                json!({ "type": "array", "items": { "type": "string" } }),
+            ) },
+        } },
+        "/api/snapshot": {
+            "get": {
+                "summary": "The current state of all values",
+                "responses": { "200": json_response("The snapshot", schema("Snapshot")) },
+            },
+            "post": {
+                "summary": "Restore a snapshot, either all or none of the values are restored, read-only values are skipped",
+                "requestBody": json_body(schema("Snapshot")),
+                "responses": with_errors(ok.clone()),
+            },
+        },
+        "/api/load-state": { "get": {
+            "summary": "Restore a base64 encoded snapshot",
+            "parameters": [{
+                "name": "state",
+                "in": "query",
+                "required": true,
+                "schema": { "type": "string", "format": "byte" },
+            }],
+            "responses": with_errors(ok.clone()),
+        } },
+        "/api/export.csv": { "get": {
+            "summary": "Describe all values as CSV",
+            "responses": { "200": {
+                "description": "The columns are key,type,default,current,min,max,description,locked",
+                "content": { "text/csv": { "schema": { "type": "string" } } },
+            } },
+        } },
+        "/api/presets": { "get": {
+            "summary": "The names of the saved presets",
+            "responses": { "200": json_response(
+                "The names",
+                json!({ "type": "array", "items": { "type": "string" } }),
+            ) },
+        } },
+        "/api/presets/{name}": {
+            "get": {
+                "summary": "Load a preset",
+                "parameters": name_parameter.clone(),
+                "responses": with_errors(json!({ "description": "The preset is loaded" })),
+            },
+            "post": {
+                "summary": "Save the current values as a preset",
+                "parameters": name_parameter.clone(),
+                "responses": { "200": json_response("The saved snapshot", schema("Snapshot")) },
+            },
+            "delete": {
+                "summary": "Delete a preset",
+                "parameters": name_parameter,
+                "responses": with_errors(json!({ "description": "The preset is deleted" })),
+            },
+        },
+        "/log": { "get": {
+            "summary": "The most recent changes, newest first",
+            "parameters": [{
+                "name": "key",
+                "in": "query",
+                "required": false,
+                "schema": schema("Key"),
+            }],
+            "responses": { "200": json_response(
+                "The changes",
+                json!({ "type": "array", "items": schema("ChangeLogEntry") }),
+            ) },
+        } },
+        "/health": { "get": {
+            "summary": "Liveness probe",
+            "security": [],
+            "responses": { "200": json_response(
+                "The web server is alive",
+                json!({ "type": "object", "properties": { "status": { "type": "string" } } }),
+            ) },
+        } },
+        "/ready": { "get": {
+            "summary": "Readiness probe",
+            "security": [],
+            "responses": {
+                "200": json_response(
+                    "The web server accepts connections",
+                    json!({ "type": "object", "properties": { "ready": { "type": "boolean" } } }),
+                ),
+                "503": { "description": "The web server is shutting down" },
+            },
+        } },
+        "/openapi.json": { "get": {
+            "summary": "This document",
+            "responses": { "200": { "description": "The OpenAPI document" } },
+        } },
+    });
+    if let serde_json::Value::Object(other_paths) = other_paths {
+        paths.extend(other_paths);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "const-tweaker",
+            "description": "Tweak constant variables live from a web GUI",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        // The token is only required when it's configured
+        "security": [{}, { "bearerAuth": [] }],
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+            },
+            "responses": {
+                "BadRequest": {
+                    "description": "The request or the value is invalid",
+                    "content": {
+                        "application/json": { "schema": schema("Error") },
+                        "text/plain": { "schema": { "type": "string" } },
+                    },
+                },
+                "NotFound": {
+                    "description": "No value with the key is registered",
+                    "content": { "text/plain": { "schema": { "type": "string" } } },
+                },
+                "Locked": {
+                    "description": "The value is locked",
+                    "content": { "text/plain": { "schema": { "type": "string" } } },
+                },
+            },
+            "schemas": {
+                "Key": {
+                    "type": "string",
+                    "description": "The key of a registered value",
+                    "enum": keys,
+                },
+                "Value": {
+                    "description": "A value, large integers are strings & custom types are objects",
+                },
+                "Entry": {
+                    "type": "object",
+                    "required": ["type", "value"],
+                    "properties": {
+                        "type": { "type": "string" },
+                        "value": schema("Value"),
+                        "default": schema("Value"),
+                        "min": schema("Value"),
+                        "max": schema("Value"),
+                    },
+                },
+                "Values": {
+                    "type": "object",
+                    "description": "The values by key",
+                    "additionalProperties": schema("Entry"),
+                },
+                "Snapshot": {
+                    "type": "object",
+                    "description": "The types & values by key",
+                    "additionalProperties": {
+                        "type": "object",
+                        "required": ["type", "value"],
+                        "properties": {
+                            "type": { "type": "string" },
+                            "value": schema("Value"),
+                        },
+                    },
+                },
+                "NewValue": {
+                    "type": "object",
+                    "properties": { "value": schema("Value") },
+                },
+                "TweakInfo": {
+                    "type": "object",
+                    "properties": {
+                        "key": { "type": "string" },
+                        "type_name": { "type": "string" },
+                        "description": { "type": "string", "nullable": true },
+                        "default": schema("Value"),
+                        "current": schema("Value"),
+                        "min": schema("Value"),
+                        "max": schema("Value"),
+                        "locked": { "type": "boolean" },
+                        "hidden": { "type": "boolean" },
+                        "readonly": { "type": "boolean" },
+                        "change_count": { "type": "integer" },
+                    },
+                },
+                "ChangeLogEntry": {
+                    "type": "object",
+                    "properties": {
+                        "timestamp": {
+                            "type": "integer",
+                            "description": "Milliseconds since the UNIX epoch",
+                        },
+                        "key": { "type": "string" },
+                        "old_value": schema("Value"),
+                        "new_value": schema("Value"),
+                        "source_ip": {
+                            "type": "string",
+                            "nullable": true,
+                            "description": "The client that changed the value, null for changes \
+                                from code & connections on a Unix socket",
+                        },
+                    },
+                },
+                "Error": {
+                    "type": "object",
+                    "properties": { "error": { "type": "string" } },
+                },
+            },
+        },
+    })
+}
@@ -0,0 +1,47 @@
+use const_tweaker::TweakerConfig;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    sync::Mutex,
+};
+
+/// Only a single server can run at the same time, so the tests can't run in parallel.
+static SERVER: Mutex<()> = Mutex::new(());
+
+/// Request the web page and return the HTTP status code.
+fn get_page(port: u16) -> u16 {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    write!(
+        stream,
+        "GET / HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response[9..12].parse().unwrap()
+}
+
+#[test]
+fn refuse_unknown_address() {
+    let _server = SERVER.lock().unwrap();
+
+    let config = TweakerConfig::builder()
+        .port(0)
+        .ip_allowlist(vec!["10.0.0.0/8".parse().unwrap()])
+        .build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    assert_eq!(get_page(tweaker.port()), 403);
+    tweaker.shutdown().expect("Could not shutdown server");
+}
+
+#[test]
+fn allow_any_ip() {
+    let _server = SERVER.lock().unwrap();
+
+    let config = TweakerConfig::builder().port(0).allow_any_ip().build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    assert_eq!(get_page(tweaker.port()), 200);
+    tweaker.shutdown().expect("Could not shutdown server");
+}
@@ -0,0 +1,722 @@
+use serde_json::{json, Value};
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicU16, AtomicU64, Ordering},
+        Once,
+    },
+};
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const GRAVITY: f64 = 9.8;
+
+#[const_tweaker::tweak]
+const SEED: i64 = 0;
+
+#[const_tweaker::tweak]
+const ENABLED: bool = false;
+
+static START: Once = Once::new();
+static PORT: AtomicU16 = AtomicU16::new(0);
+
+/// Send a request to the tweaker server and return the HTTP status code & the body.
+fn request(method: &str, path: &str, body: &str) -> (u16, String) {
+    START.call_once(|| {
+        let tweaker = const_tweaker::run_on_random_port().expect("Could not run server");
+        PORT.store(tweaker.port(), Ordering::SeqCst);
+    });
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT.load(Ordering::SeqCst)))
+        .expect("Could not connect to server");
+    // HTTP/1.0 is used so the response body isn't chunked
+    write!(
+        stream,
+        "{} {} HTTP/1.0\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let body = response
+        .split("\r\n\r\n")
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    (response[9..12].parse().unwrap(), body)
+}
+
+#[test]
+fn get_values() {
+    // Register the values
+    assert_eq!(*GRAVITY, 9.8);
+
+    let (status, body) = request("GET", "/api/values", "");
+    assert_eq!(status, 200);
+
+    let values: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        values["GRAVITY"],
+        json!({ "type": "f64", "value": 9.8, "default": 9.8, "min": 0.0, "max": 20.0 })
+    );
+}
+
+#[test]
+fn set_values() {
+    assert_eq!(*SEED, 0);
+    assert!(!*ENABLED);
+
+    let body = json!({
+        "SEED": { "value": "-9223372036854775808" },
+        "ENABLED": { "value": true },
+    });
+    assert_eq!(request("POST", "/api/values", &body.to_string()).0, 200);
+    assert_eq!(*SEED, i64::MIN);
+    assert!(*ENABLED);
+
+    // Nothing is changed when one of the values is invalid
+    let body = json!({
+        "SEED": { "value": "1" },
+        "ENABLED": { "value": 1 },
+    });
+    assert_eq!(request("POST", "/api/values", &body.to_string()).0, 400);
+    assert_eq!(*SEED, i64::MIN);
+    assert!(*ENABLED);
+}
+
+#[const_tweaker::tweak]
+const LOGGED: u8 = 1;
+
+#[test]
+fn change_log() {
+    assert_eq!(*LOGGED, 1);
+
+    let (status, _) = request("POST", "/api/values", r#"{ "LOGGED": { "value": 2 } }"#);
+    assert_eq!(status, 200);
+    let (status, _) = request("POST", "/api/values", r#"{ "LOGGED": { "value": 3 } }"#);
+    assert_eq!(status, 200);
+
+    let (status, body) = request("GET", "/log?key=LOGGED", "");
+    assert_eq!(status, 200);
+    let entries: Vec<Value> = serde_json::from_str(&body).unwrap();
+    assert_eq!(entries.len(), 2);
+    // Newest first
+    assert_eq!(entries[0]["old_value"], json!(2));
+    assert_eq!(entries[0]["new_value"], json!(3));
+    assert_eq!(entries[1]["old_value"], json!(1));
+    assert!(entries.iter().all(|entry| entry["key"] == "LOGGED"));
+    assert!(entries
+        .iter()
+        .all(|entry| entry["source_ip"] == json!("127.0.0.1")));
+
+    // Changes from code have no source
+    assert!(LOGGED.set(4));
+    let (_, body) = request("GET", "/log?key=LOGGED", "");
+    let entries: Vec<Value> = serde_json::from_str(&body).unwrap();
+    assert_eq!(entries[0]["new_value"], json!(4));
+    assert_eq!(entries[0]["source_ip"], Value::Null);
+}
+
+#[const_tweaker::tweak]
+const SNAPSHOT_VALUE: f64 = 0.5;
+
+#[test]
+fn snapshot() {
+    assert_eq!(*SNAPSHOT_VALUE, 0.5);
+
+    let (status, body) = request("GET", "/api/snapshot", "");
+    assert_eq!(status, 200);
+    let snapshot: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        snapshot["SNAPSHOT_VALUE"],
+        json!({ "type": "f64", "value": 0.5 })
+    );
+
+    assert!(const_tweaker::set_f64("SNAPSHOT_VALUE", 0.25));
+    let body = json!({ "SNAPSHOT_VALUE": snapshot["SNAPSHOT_VALUE"] });
+    assert_eq!(request("POST", "/api/snapshot", &body.to_string()).0, 200);
+    assert_eq!(*SNAPSHOT_VALUE, 0.5);
+
+    // The type has to match the registered value
+    let body = json!({ "SNAPSHOT_VALUE": { "type": "f32", "value": 0.25 } });
+    assert_eq!(request("POST", "/api/snapshot", &body.to_string()).0, 400);
+    assert_eq!(*SNAPSHOT_VALUE, 0.5);
+}
+
+#[test]
+fn schema() {
+    assert_eq!(*GRAVITY, 9.8);
+
+    let (status, body) = request("GET", "/api/schema", "");
+    assert_eq!(status, 200);
+    let schema: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let gravity = schema
+        .iter()
+        .find(|info| info["key"] == "GRAVITY")
+        .expect("GRAVITY is missing");
+    assert_eq!(gravity["type_name"], "f64");
+    assert_eq!(gravity["default"], json!(9.8));
+    assert_eq!(gravity["min"], json!(0.0));
+    assert_eq!(gravity["max"], json!(20.0));
+    assert_eq!(gravity["locked"], json!(false));
+    assert_eq!(gravity["hidden"], json!(false));
+}
+
+#[const_tweaker::tweak]
+const TINT: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+
+#[test]
+fn set_color() {
+    assert_eq!(TINT.get(), [0.0, 0.0, 0.0, 0.5]);
+
+    // The alpha is kept when it's not sent
+    let body = json!({ "key": "TINT", "value": "#ff0000" });
+    assert_eq!(request("POST", "/set/color", &body.to_string()).0, 200);
+    assert_eq!(TINT.get(), [1.0, 0.0, 0.0, 0.5]);
+
+    let body = json!({ "key": "TINT", "value": "#00ff00ff" });
+    assert_eq!(request("POST", "/set/color", &body.to_string()).0, 200);
+    assert_eq!(TINT.get(), [0.0, 1.0, 0.0, 1.0]);
+
+    let body = json!({ "key": "TINT", "value": "green" });
+    assert_eq!(request("POST", "/set/color", &body.to_string()).0, 400);
+
+    // Only colors can be set
+    assert_eq!(*GRAVITY, 9.8);
+    let body = json!({ "key": "GRAVITY", "value": "#ff0000" });
+    assert_eq!(request("POST", "/set/color", &body.to_string()).0, 400);
+}
+
+#[const_tweaker::tweak]
+const OVERRIDE_FOV: Option<f64> = None;
+
+#[const_tweaker::tweak]
+const OVERRIDE_VSYNC: Option<bool> = Some(true);
+
+#[test]
+fn set_optional() {
+    assert_eq!(OVERRIDE_FOV.get(), None);
+    assert_eq!(OVERRIDE_VSYNC.get(), Some(true));
+
+    let body = json!({ "key": "OVERRIDE_FOV", "enabled": true, "value": 90.0 });
+    assert_eq!(
+        request("POST", "/set/optional_f64", &body.to_string()).0,
+        200
+    );
+    assert_eq!(OVERRIDE_FOV.get(), Some(90.0));
+
+    // The value is ignored when it's disabled
+    let body = json!({ "key": "OVERRIDE_FOV", "enabled": false, "value": 60.0 });
+    assert_eq!(
+        request("POST", "/set/optional_f64", &body.to_string()).0,
+        200
+    );
+    assert_eq!(OVERRIDE_FOV.get(), None);
+
+    let body = json!({ "key": "OVERRIDE_FOV", "enabled": true });
+    assert_eq!(
+        request("POST", "/set/optional_f64", &body.to_string()).0,
+        400
+    );
+
+    let body = json!({ "key": "OVERRIDE_VSYNC", "enabled": false });
+    assert_eq!(
+        request("POST", "/set/optional_bool", &body.to_string()).0,
+        200
+    );
+    assert_eq!(OVERRIDE_VSYNC.get(), None);
+
+    // Only values of the matching type can be set
+    let body = json!({ "key": "OVERRIDE_VSYNC", "enabled": true, "value": 1.0 });
+    assert_eq!(
+        request("POST", "/set/optional_f64", &body.to_string()).0,
+        400
+    );
+
+    let (_, page) = request("GET", "/", "");
+    assert!(page.contains(r#"id="OVERRIDE_FOV::enabled""#));
+}
+
+#[const_tweaker::tweak]
+const ANIMATION_DURATION: std::time::Duration = std::time::Duration::from_millis(300);
+
+#[test]
+fn set_duration() {
+    use std::time::Duration;
+
+    assert_eq!(ANIMATION_DURATION.get(), Duration::from_millis(300));
+
+    let body = json!({ "ANIMATION_DURATION": { "value": 1500 } });
+    assert_eq!(request("POST", "/api/values", &body.to_string()).0, 200);
+    assert_eq!(ANIMATION_DURATION.get(), Duration::from_millis(1500));
+
+    // Only whole non-negative milliseconds are allowed
+    for value in &[json!(-1), json!(0.5), json!(1e20)] {
+        let body = json!({ "ANIMATION_DURATION": { "value": value } });
+        assert_eq!(request("POST", "/api/values", &body.to_string()).0, 400);
+    }
+    assert_eq!(ANIMATION_DURATION.get(), Duration::from_millis(1500));
+
+    let (_, page) = request("GET", "/", "");
+    assert!(page.contains(r#"<span class="button is-small is-static">ms</span>"#));
+}
+
+#[const_tweaker::tweak]
+const COEFFICIENTS: Vec<f64> = vec![1.0, 0.0, 0.0];
+
+#[test]
+fn set_vec_f64() {
+    assert_eq!(COEFFICIENTS.get(), vec![1.0, 0.0, 0.0]);
+
+    let body = json!({ "key": "COEFFICIENTS", "value": [1.0, 0.5, 0.25, 0.125] });
+    assert_eq!(request("POST", "/set/vec_f64", &body.to_string()).0, 200);
+    assert_eq!(COEFFICIENTS.get(), vec![1.0, 0.5, 0.25, 0.125]);
+
+    let body = json!({ "key": "COEFFICIENTS", "value": [] });
+    assert_eq!(request("POST", "/set/vec_f64", &body.to_string()).0, 200);
+    assert_eq!(COEFFICIENTS.get(), Vec::<f64>::new());
+
+    let body = json!({ "key": "COEFFICIENTS", "value": ["a"] });
+    assert_eq!(request("POST", "/set/vec_f64", &body.to_string()).0, 400);
+
+    // Only lists can be set
+    assert_eq!(*GRAVITY, 9.8);
+    let body = json!({ "key": "GRAVITY", "value": [1.0] });
+    assert_eq!(request("POST", "/set/vec_f64", &body.to_string()).0, 400);
+
+    let (_, page) = request("GET", "/", "");
+    assert!(page.contains(r#"id="COEFFICIENTS::elements""#));
+    assert!(page.contains("Remove last element"));
+}
+
+#[const_tweaker::tweak(group = "Physics")]
+const FRICTION: f64 = 0.5;
+
+#[const_tweaker::tweak(group = "Rendering")]
+const FOG_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+
+#[test]
+fn groups() {
+    assert_eq!(*FRICTION, 0.5);
+    assert_eq!(FOG_COLOR.get(), [0.5, 0.5, 0.5, 1.0]);
+    assert_eq!(*GRAVITY, 9.8);
+
+    let (status, body) = request("GET", "/api/groups", "");
+    assert_eq!(status, 200);
+    let groups: Vec<String> = serde_json::from_str(&body).unwrap();
+    // The values without a group are grouped by the file name
+    assert_eq!(groups, vec!["Physics", "Rendering", "api"]);
+
+    let (status, body) = request("GET", "/", "");
+    assert_eq!(status, 200);
+    // The groups show the amount of values they contain
+    assert!(body.contains(">Physics (1)</summary>"));
+    assert!(body.contains(">api ("));
+
+    // The dark mode toggle
+    assert!(body.contains(r#"role="switch""#));
+}
+
+/// Drag coefficient
+/// of the air.
+#[const_tweaker::tweak]
+const DRAG: f64 = 0.1;
+
+#[test]
+fn description() {
+    assert_eq!(*DRAG, 0.1);
+
+    let (_, body) = request("GET", "/api/schema", "");
+    let schema: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let find = |key: &str| {
+        schema
+            .iter()
+            .find(|info| info["key"] == key)
+            .cloned()
+            .unwrap_or_default()
+    };
+    assert_eq!(find("DRAG")["description"], "Drag coefficient\nof the air.");
+
+    let (_, body) = request("GET", "/", "");
+    assert!(body.contains(r#"title="Drag coefficient"#));
+
+    // The filter searches in the key, the description & the group
+    assert!(body.contains(r#"id="search""#));
+    assert!(body.contains("data-search=\"drag drag coefficient\nof the air. api\""));
+}
+
+#[const_tweaker::tweak]
+const DIFF_CHANGED: u16 = 1;
+
+#[const_tweaker::tweak]
+const DIFF_UNCHANGED: u16 = 1;
+
+#[test]
+fn diff() {
+    assert_eq!(*DIFF_CHANGED, 1);
+    assert_eq!(*DIFF_UNCHANGED, 1);
+
+    let body = json!({ "DIFF_CHANGED": { "value": 2 } });
+    assert_eq!(request("POST", "/api/values", &body.to_string()).0, 200);
+
+    let (status, body) = request("GET", "/api/diff", "");
+    assert_eq!(status, 200);
+    let diff: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(diff["DIFF_CHANGED"]["value"], json!(2));
+    assert_eq!(diff["DIFF_CHANGED"]["default"], json!(1));
+    assert!(diff.get("DIFF_UNCHANGED").is_none());
+
+    // Modified values are highlighted
+    let (_, body) = request("GET", "/", "");
+    assert!(body.contains(r#"class="columns box tweak modified" data-key="DIFF_CHANGED""#));
+    assert!(body.contains(r#"class="columns box tweak" data-key="DIFF_UNCHANGED""#));
+}
+
+#[const_tweaker::tweak]
+const DEFAULT_CHANGED: f64 = 0.5;
+
+#[test]
+fn defaults() {
+    assert_eq!(*DEFAULT_CHANGED, 0.5);
+    assert!(const_tweaker::set_f64("DEFAULT_CHANGED", 0.25));
+    assert_eq!(const_tweaker::get_default_f64("DEFAULT_CHANGED"), Some(0.5));
+    assert_eq!(const_tweaker::get_default_bool("DEFAULT_CHANGED"), None);
+
+    let (status, body) = request("GET", "/api/defaults", "");
+    assert_eq!(status, 200);
+    let defaults: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        defaults["DEFAULT_CHANGED"],
+        json!({ "type": "f64", "default": 0.5 })
+    );
+}
+
+#[const_tweaker::tweak(min = 0.0, max = 1.0)]
+const NUDGED: f64 = 0.5;
+
+#[const_tweaker::tweak]
+const TOGGLED: bool = false;
+
+#[test]
+fn delta() {
+    assert_eq!(*NUDGED, 0.5);
+    assert!(!*TOGGLED);
+
+    let body = json!({ "key": "NUDGED", "delta": 0.25 });
+    let (status, body) = request("POST", "/api/delta/f64", &body.to_string());
+    assert_eq!(status, 200);
+    assert_eq!(
+        serde_json::from_str::<Value>(&body).unwrap(),
+        json!({ "value": 0.75 })
+    );
+
+    // The value is clamped to the bounds
+    assert_eq!(const_tweaker::apply_delta_f64("NUDGED", -2.0), Some(0.0));
+    assert_eq!(const_tweaker::apply_delta_f64("MISSING", 1.0), None);
+
+    let body = json!({ "key": "TOGGLED" });
+    let (status, body) = request("POST", "/api/toggle/bool", &body.to_string());
+    assert_eq!(status, 200);
+    assert_eq!(
+        serde_json::from_str::<Value>(&body).unwrap(),
+        json!({ "value": true })
+    );
+    assert_eq!(const_tweaker::toggle_bool("TOGGLED"), Some(false));
+
+    // Only booleans can be toggled
+    let body = json!({ "key": "NUDGED" });
+    assert_eq!(
+        request("POST", "/api/toggle/bool", &body.to_string()).0,
+        400
+    );
+}
+
+#[const_tweaker::tweak]
+const PRESET_VALUE: u32 = 1;
+
+#[test]
+fn presets() {
+    assert_eq!(*PRESET_VALUE, 1);
+
+    assert_eq!(request("POST", "/api/presets/low%20quality", "").0, 200);
+    let (status, body) = request("GET", "/api/presets", "");
+    assert_eq!(status, 200);
+    let names: Vec<String> = serde_json::from_str(&body).unwrap();
+    assert!(names.contains(&"low quality".to_string()));
+
+    let body = json!({ "PRESET_VALUE": { "value": 2 } });
+    assert_eq!(request("POST", "/api/values", &body.to_string()).0, 200);
+    assert_eq!(*PRESET_VALUE, 2);
+
+    assert_eq!(request("GET", "/api/presets/low%20quality", "").0, 200);
+    assert_eq!(*PRESET_VALUE, 1);
+
+    assert_eq!(request("DELETE", "/api/presets/low%20quality", "").0, 200);
+    assert_eq!(request("GET", "/api/presets/low%20quality", "").0, 404);
+    assert_eq!(request("DELETE", "/api/presets/low%20quality", "").0, 404);
+}
+
+#[const_tweaker::tweak]
+const SHARED_VALUE: u8 = 1;
+
+#[test]
+fn load_state() {
+    assert_eq!(*SHARED_VALUE, 1);
+
+    // {"SHARED_VALUE":{"type":"u8","value":7}}
+    let state = "eyJTSEFSRURfVkFMVUUiOnsidHlwZSI6InU4IiwidmFsdWUiOjd9fQ";
+    let (status, _) = request("GET", &format!("/api/load-state?state={}", state), "");
+    assert_eq!(status, 200);
+    assert_eq!(*SHARED_VALUE, 7);
+
+    assert_eq!(request("GET", "/api/load-state?state=%%%", "").0, 400);
+    assert_eq!(request("GET", "/api/load-state", "").0, 400);
+}
+
+#[test]
+fn export_csv() {
+    assert_eq!(*GRAVITY, 9.8);
+
+    let (status, body) = request("GET", "/api/export.csv", "");
+    assert_eq!(status, 200);
+    assert!(body.starts_with("key,type,default,current,min,max,description,locked\n"));
+    assert!(body.contains("\nGRAVITY,f64,9.8,"));
+}
+
+#[test]
+fn openapi() {
+    assert_eq!(*GRAVITY, 9.8);
+
+    let (status, body) = request("GET", "/openapi.json", "");
+    assert_eq!(status, 200);
+    let document: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(document["openapi"], "3.0.3");
+    assert!(document["paths"]["/set/f64"]["post"].is_object());
+    assert!(document["paths"]["/api/values"]["get"].is_object());
+
+    // The registered keys are listed
+    let keys = document["components"]["schemas"]["Key"]["enum"]
+        .as_array()
+        .unwrap();
+    assert!(keys.contains(&json!("GRAVITY")));
+
+    let (status, body) = request("GET", "/api/docs", "");
+    assert_eq!(status, 200);
+    assert!(body.contains("/openapi.json"));
+}
+
+#[test]
+fn health_checks() {
+    assert_eq!(
+        request("GET", "/health", ""),
+        (200, json!({ "status": "ok" }).to_string())
+    );
+    assert_eq!(
+        request("GET", "/ready", ""),
+        (200, json!({ "ready": true }).to_string())
+    );
+}
+
+#[const_tweaker::tweak(hidden)]
+const HIDDEN_SEED: i64 = 42;
+
+#[test]
+fn hidden_value() {
+    assert_eq!(*HIDDEN_SEED, 42);
+
+    let (status, page) = request("GET", "/", "");
+    assert_eq!(status, 200);
+    assert!(!page.contains("HIDDEN_SEED"));
+
+    let (_, body) = request("GET", "/api/schema", "");
+    let schema: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let seed = schema
+        .iter()
+        .find(|info| info["key"] == "HIDDEN_SEED")
+        .expect("HIDDEN_SEED is missing");
+    assert_eq!(seed["hidden"], json!(true));
+
+    // It can still be changed with the API
+    let body = json!({ "key": "HIDDEN_SEED", "value": "7" });
+    assert_eq!(request("POST", "/set/i64", &body.to_string()).0, 200);
+    assert_eq!(*HIDDEN_SEED, 7);
+}
+
+#[const_tweaker::tweak(readonly)]
+const FRAME_TIME: f64 = 0.0;
+
+#[test]
+fn readonly_value() {
+    assert_eq!(*FRAME_TIME, 0.0);
+
+    let body = json!({ "key": "FRAME_TIME", "value": 1.0 });
+    assert_eq!(request("POST", "/set/f64", &body.to_string()).0, 403);
+    let body = json!({ "FRAME_TIME": { "value": 1.0 } });
+    assert_eq!(request("POST", "/api/values", &body.to_string()).0, 403);
+    assert_eq!(*FRAME_TIME, 0.0);
+
+    // The program can still change it
+    assert!(const_tweaker::set_f64("FRAME_TIME", 16.5));
+    assert_eq!(*FRAME_TIME, 16.5);
+
+    // It's shown as text instead of a slider
+    let (_, page) = request("GET", "/", "");
+    assert!(page.contains(r#"<span id="FRAME_TIME_label" class="readonly">16.5</span>"#));
+}
+
+#[const_tweaker::tweak(readonly)]
+const RENDER_TIME: f64 = 0.0;
+
+#[test]
+fn readonly_snapshot() {
+    assert_eq!(*RENDER_TIME, 0.0);
+    assert!(const_tweaker::set_f64("RENDER_TIME", 4.0));
+
+    // Read-only values in a snapshot are skipped
+    let body = json!({ "RENDER_TIME": { "type": "f64", "value": 1.0 } });
+    assert_eq!(request("POST", "/api/snapshot", &body.to_string()).0, 200);
+    assert_eq!(*RENDER_TIME, 4.0);
+
+    // Also when a preset is loaded from the web interface
+    const_tweaker::save_preset("readonly");
+    assert!(const_tweaker::set_f64("RENDER_TIME", 8.0));
+    assert_eq!(request("GET", "/api/presets/readonly", "").0, 200);
+    assert_eq!(*RENDER_TIME, 8.0);
+}
+
+#[test]
+fn list() {
+    assert_eq!(*GRAVITY, 9.8);
+
+    let (status, body) = request("GET", "/api/list", "");
+    assert_eq!(status, 200);
+    let list: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let gravity = list
+        .iter()
+        .find(|row| row["key"] == "GRAVITY")
+        .expect("GRAVITY is missing");
+    assert_eq!(gravity["type"], "f64");
+    assert_eq!(gravity["default"], json!(9.8));
+
+    // The rows are sorted by key
+    let keys: Vec<&str> = list
+        .iter()
+        .map(|row| row["key"].as_str().unwrap())
+        .collect();
+    assert!(keys.windows(2).all(|keys| keys[0] < keys[1]));
+}
+
+#[test]
+fn float_number_input() {
+    assert_eq!(*GRAVITY, 9.8);
+
+    let (_, page) = request("GET", "/", "");
+    assert!(page.contains(r#"id="GRAVITY_number""#));
+    assert!(page.contains(r#"step="any""#));
+    assert!(page.contains("var significant_digits = 6; var debounce_ms = 50;"));
+}
+
+#[const_tweaker::tweak(min = 0.001, max = 1000.0, scale = "log")]
+const LOG_SCALE: f64 = 1.0;
+
+#[test]
+fn log_scale_slider() {
+    assert_eq!(*LOG_SCALE, 1.0);
+
+    let (_, page) = request("GET", "/", "");
+    let slider = page
+        .split("<input")
+        .find(|input| input.contains(r#"id="LOG_SCALE""#))
+        .expect("LOG_SCALE is missing");
+    assert!(slider.contains(r#"min="-3""#));
+    assert!(slider.contains(r#"max="3""#));
+    assert!(slider.contains(r#"data-scale="log""#));
+    assert!(slider.contains(
+        r#"oninput="send_debounced('LOG_SCALE', Math.pow(10, Number(this.value)), 'f64')""#
+    ));
+    assert!(
+        slider.contains(r#"onchange="send('LOG_SCALE', Math.pow(10, Number(this.value)), 'f64')""#)
+    );
+
+    // The value itself isn't on a logarithmic scale
+    let body = json!({ "key": "LOG_SCALE", "value": 100.0 });
+    assert_eq!(request("POST", "/set/f64", &body.to_string()).0, 200);
+    assert_eq!(*LOG_SCALE, 100.0);
+}
+
+/// The friction when the gravity changed, as the bits of the `f64`.
+static FRICTION_ON_CHANGE: AtomicU64 = AtomicU64::new(0);
+
+fn store_friction(_: f64) {
+    FRICTION_ON_CHANGE.store(BATCH_FRICTION.to_bits(), Ordering::SeqCst);
+}
+
+#[const_tweaker::tweak(on_change = "store_friction")]
+const BATCH_GRAVITY: f64 = 0.0;
+
+#[const_tweaker::tweak]
+const BATCH_FRICTION: f64 = 0.0;
+
+#[test]
+fn batch() {
+    assert_eq!(*BATCH_GRAVITY, 0.0);
+    assert_eq!(*BATCH_FRICTION, 0.0);
+
+    let body = json!([
+        { "key": "BATCH_GRAVITY", "type": "f64", "value": 0.5 },
+        { "key": "BATCH_FRICTION", "type": "bool", "value": true },
+        { "key": "BATCH_FRICTION", "type": "f64", "value": 0.25 },
+        { "key": "MISSING_VALUE", "type": "f64", "value": 0.25 },
+    ]);
+    let (status, body) = request("POST", "/api/batch", &body.to_string());
+    assert_eq!(status, 200);
+    let results: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let ok: Vec<&Value> = results.iter().map(|result| &result["ok"]).collect();
+    assert_eq!(
+        ok,
+        vec![&json!(true), &json!(false), &json!(true), &json!(false)]
+    );
+    assert_eq!(results[1]["error"], "the value is a f64, not a bool");
+
+    assert_eq!(*BATCH_GRAVITY, 0.5);
+    assert_eq!(*BATCH_FRICTION, 0.25);
+
+    // The callback is only called after all values are stored
+    assert_eq!(
+        f64::from_bits(FRICTION_ON_CHANGE.load(Ordering::SeqCst)),
+        0.25
+    );
+}
+
+#[const_tweaker::tweak]
+const COUNTED_VALUE: bool = false;
+
+#[test]
+fn change_count() {
+    assert!(!*COUNTED_VALUE);
+    assert_eq!(const_tweaker::change_count("COUNTED_VALUE"), 0);
+
+    let body = json!({ "key": "COUNTED_VALUE", "value": true });
+    assert_eq!(request("POST", "/set/bool", &body.to_string()).0, 200);
+    assert!(const_tweaker::set_bool("COUNTED_VALUE", false));
+
+    // Resetting is counted as a change instead of resetting the count
+    assert_eq!(request("POST", "/api/reset/COUNTED_VALUE", "").0, 200);
+    assert_eq!(const_tweaker::change_count("COUNTED_VALUE"), 3);
+
+    let (_, body) = request("GET", "/api/schema", "");
+    let schema: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let info = schema
+        .iter()
+        .find(|info| info["key"] == "COUNTED_VALUE")
+        .expect("COUNTED_VALUE is missing");
+    assert_eq!(info["change_count"], json!(3));
+
+    let (_, page) = request("GET", "/", "");
+    assert!(page.contains(r#"id="COUNTED_VALUE_changes""#));
+}
@@ -0,0 +1,44 @@
+use const_tweaker::TweakerConfig;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+/// Request a page with optional extra headers and return the HTTP status code.
+fn get(port: u16, path: &str, headers: &str) -> u16 {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\n{}Connection: close\r\n\r\n",
+        path, headers
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response[9..12].parse().unwrap()
+}
+
+#[test]
+fn bearer_token() {
+    let config = TweakerConfig::builder()
+        .port(0)
+        .auth_token(Some("secret".to_string()))
+        .build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    let port = tweaker.port();
+
+    assert_eq!(get(port, "/", ""), 401);
+    assert_eq!(get(port, "/", "Authorization: Bearer wrong\r\n"), 401);
+    assert_eq!(get(port, "/?token=wrong", ""), 401);
+
+    assert_eq!(get(port, "/", "Authorization: Bearer secret\r\n"), 200);
+    assert_eq!(get(port, "/?token=secret", ""), 200);
+
+    // Health checks don't need the token
+    assert_eq!(get(port, "/health", ""), 200);
+    assert_eq!(get(port, "/ready", ""), 200);
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
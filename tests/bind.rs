@@ -0,0 +1,112 @@
+use const_tweaker::TweakerConfig;
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream},
+    sync::Mutex,
+};
+
+/// Only a single server can run at the same time, so the tests can't run in parallel.
+static SERVER: Mutex<()> = Mutex::new(());
+
+/// Request the web page from the address and return the HTTP status code.
+fn get_page(addr: SocketAddr) -> u16 {
+    let mut stream = TcpStream::connect(addr).expect("Could not connect to server");
+    write!(
+        stream,
+        "GET / HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        addr
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response[9..12].parse().unwrap()
+}
+
+#[test]
+fn ipv4_loopback() {
+    let _server = SERVER.lock().unwrap();
+
+    let config = TweakerConfig::builder().port(0).build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    assert_eq!(
+        tweaker.url(),
+        format!("http://127.0.0.1:{}", tweaker.port())
+    );
+    assert_eq!(get_page(tweaker.addr()), 200);
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
+
+#[test]
+fn ipv6_loopback() {
+    let _server = SERVER.lock().unwrap();
+
+    let config = TweakerConfig::builder()
+        .host("::1".parse().unwrap())
+        .port(0)
+        .build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    assert_eq!(tweaker.url(), format!("http://[::1]:{}", tweaker.port()));
+    assert_eq!(get_page(tweaker.addr()), 200);
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
+
+#[test]
+fn ipv6_unspecified() {
+    let _server = SERVER.lock().unwrap();
+
+    let config = TweakerConfig::builder()
+        .host(IpAddr::V6(Ipv6Addr::UNSPECIFIED))
+        .port(0)
+        .build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    assert_eq!(tweaker.url(), format!("http://[::]:{}", tweaker.port()));
+
+    let loopback = SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), tweaker.port());
+    assert_eq!(get_page(loopback), 200);
+
+    // Whether IPv4 connections are accepted depends on the operating system, but when they are
+    // the mapped addresses are allowed like IPv4 addresses
+    let ipv4 = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), tweaker.port());
+    if TcpStream::connect(ipv4).is_ok() {
+        assert_eq!(get_page(ipv4), 200);
+    }
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
+
+#[test]
+#[cfg(unix)]
+fn unix_socket() {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let _server = SERVER.lock().unwrap();
+
+    let path = std::env::temp_dir().join(format!("const-tweaker-{}.sock", std::process::id()));
+    // A socket file left behind by a server that's no longer running is replaced
+    drop(UnixListener::bind(&path).unwrap());
+
+    let config = TweakerConfig::builder().unix_socket(&path).build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    assert_eq!(tweaker.unix_socket(), Some(path.as_path()));
+    // The slashes of the path are encoded
+    assert!(tweaker.url().starts_with("http+unix://%2F"));
+    assert!(tweaker.url().ends_with(".sock/"));
+
+    let mut stream = UnixStream::connect(&path).expect("Could not connect to server");
+    write!(
+        stream,
+        "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert_eq!(&response[9..12], "200");
+
+    // The socket file is removed when the server stops
+    tweaker.shutdown().expect("Could not shutdown server");
+    assert!(!path.exists());
+}
@@ -0,0 +1,49 @@
+use const_tweaker::TweakerConfig;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+/// Send a POST request with the headers & the raw body and return the HTTP status code.
+fn post(port: u16, headers: &str, body: &str) -> u16 {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    write!(
+        stream,
+        "POST /api/values HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\n{}Connection: close\r\n\r\n{}",
+        headers, body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response[9..12].parse().unwrap()
+}
+
+#[test]
+fn max_body_size() {
+    let config = TweakerConfig::builder().port(0).max_body_size(64).build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+    let port = tweaker.port();
+
+    assert_eq!(post(port, "Content-Length: 2\r\n", "{}"), 200);
+
+    let body = format!(r#"{{"VALUE":{{"value":"{}"}}}}"#, "a".repeat(100));
+    let headers = format!("Content-Length: {}\r\n", body.len());
+    assert_eq!(post(port, &headers, &body), 413);
+    assert_eq!(post(port, "Content-Length: large\r\n", "{}"), 400);
+
+    // The size of chunked bodies isn't known before reading them
+    let chunked = format!("{:x}\r\n{}\r\n0\r\n\r\n", body.len(), body);
+    assert_eq!(post(port, "Transfer-Encoding: chunked\r\n", &chunked), 411);
+    assert_eq!(
+        post(
+            port,
+            "Transfer-Encoding: chunked\r\n",
+            "2\r\n{}\r\n0\r\n\r\n"
+        ),
+        411
+    );
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
@@ -0,0 +1,72 @@
+use std::{
+    convert::TryInto,
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+#[const_tweaker::tweak]
+const VALUE: f64 = 0.0;
+
+/// Request the page with extra headers and return the headers & the raw body.
+fn get_page(port: u16, headers: &str) -> (String, Vec<u8>) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    // HTTP/1.0 is used so the response body isn't chunked
+    write!(
+        stream,
+        "GET / HTTP/1.0\r\nHost: 127.0.0.1\r\n{}Connection: close\r\n\r\n",
+        headers
+    )
+    .unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+
+    let end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .expect("Response has no headers");
+    (
+        String::from_utf8_lossy(&response[..end]).to_lowercase(),
+        response[end + 4..].to_vec(),
+    )
+}
+
+/// Get the value of a header from the lowercase headers.
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers
+        .lines()
+        .find_map(|line| line.strip_prefix(&format!("{}: ", name)))
+}
+
+#[test]
+fn gzip_page() {
+    assert_eq!(*VALUE, 0.0);
+    let tweaker = const_tweaker::run_on_random_port().expect("Could not run server");
+    let port = tweaker.port();
+
+    let (headers, plain) = get_page(port, "");
+    assert!(headers.starts_with("http/1.0 200"));
+    assert_eq!(header(&headers, "content-encoding"), None);
+    assert!(String::from_utf8_lossy(&plain).contains("VALUE"));
+
+    let (headers, compressed) = get_page(port, "Accept-Encoding: deflate, gzip\r\n");
+    assert!(headers.starts_with("http/1.0 200"));
+    assert_eq!(header(&headers, "content-encoding"), Some("gzip"));
+    assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+    assert!(compressed.len() < plain.len() / 2);
+
+    // The uncompressed size is stored at the end
+    let size = u32::from_le_bytes(compressed[compressed.len() - 4..].try_into().unwrap());
+    assert_eq!(size as usize, plain.len());
+
+    // The browser already has the compressed page
+    let etag = header(&headers, "etag").expect("No ETag").to_string();
+    let (headers, body) = get_page(
+        port,
+        &format!("Accept-Encoding: gzip\r\nIf-None-Match: {}\r\n", etag),
+    );
+    assert!(headers.starts_with("http/1.0 304"));
+    assert!(body.is_empty());
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
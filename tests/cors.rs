@@ -0,0 +1,51 @@
+use const_tweaker::TweakerConfig;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+/// Send a request without a body and return the full response.
+fn request(port: u16, method: &str, path: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: 127.0.0.1\r\nOrigin: http://localhost:8000\r\nConnection: close\r\n\r\n",
+        method, path
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response.to_lowercase()
+}
+
+#[test]
+fn cors_headers() {
+    let config = TweakerConfig::builder()
+        .port(0)
+        .auth_token(Some("secret".to_string()))
+        .allow_origin("http://localhost:8000")
+        .build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+
+    // Preflight requests don't need the token
+    let response = request(tweaker.port(), "OPTIONS", "/set/f64");
+    assert!(response.starts_with("http/1.1 204"));
+    assert!(response.contains("access-control-allow-origin: http://localhost:8000\r\n"));
+    assert!(response.contains("access-control-allow-methods: get, post, delete, options\r\n"));
+    assert!(response.contains("access-control-allow-headers: authorization, content-type\r\n"));
+
+    // Locks & presets are removed with DELETE requests
+    for path in &["/api/lock/VALUE", "/api/presets/preset"] {
+        let response = request(tweaker.port(), "OPTIONS", path);
+        assert!(response.starts_with("http/1.1 204"));
+        assert!(response.contains("access-control-allow-methods: get, post, delete, options\r\n"));
+    }
+
+    let response = request(tweaker.port(), "GET", "/?token=secret");
+    assert!(response.starts_with("http/1.1 200"));
+    assert!(response.contains("access-control-allow-origin: http://localhost:8000\r\n"));
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
@@ -0,0 +1,30 @@
+// The values are counted per process, so this is the only test in this file
+
+#[const_tweaker::tweak]
+const SPEED: f64 = 1.0;
+
+#[const_tweaker::tweak]
+const GRAVITY: f64 = 9.8;
+
+#[const_tweaker::tweak]
+const FRICTION: f64 = 0.5;
+
+#[const_tweaker::tweak]
+const PAUSED: bool = false;
+
+#[const_tweaker::tweak]
+const DEBUG_DRAW: bool = false;
+
+#[test]
+fn count() {
+    // The values are registered the first time they're read
+    assert_eq!(const_tweaker::count(), 0);
+    assert_eq!((*SPEED, *GRAVITY, *FRICTION), (1.0, 9.8, 0.5));
+    assert!(!*PAUSED && !*DEBUG_DRAW);
+
+    assert_eq!(const_tweaker::count(), 5);
+    let counts = const_tweaker::count_by_type();
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts["f64"], 3);
+    assert_eq!(counts["bool"], 2);
+}
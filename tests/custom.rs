@@ -0,0 +1,152 @@
+use const_tweaker::{Tweakable, TweakerError};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Color {
+    r: f32,
+    g: f32,
+    b: f32,
+}
+
+impl Tweakable for Color {
+    fn type_name() -> &'static str {
+        "Color"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!([self.r, self.g, self.b])
+    }
+
+    fn from_json(json: serde_json::Value) -> Result<Self, TweakerError> {
+        let [r, g, b]: [f32; 3] = serde_json::from_value(json)
+            .map_err(|err| TweakerError::InvalidJson(err.to_string()))?;
+
+        Ok(Color { r, g, b })
+    }
+
+    fn render_html(key: &str, value: &Self) -> String {
+        format!("<span id=\"{}\">{:?}</span>", key, value)
+    }
+}
+
+#[const_tweaker::tweak]
+const BACKGROUND: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+};
+
+static CHANGED: AtomicBool = AtomicBool::new(false);
+
+fn foreground_changed(color: Color) {
+    assert_eq!(color.g, 1.0);
+    CHANGED.store(true, Ordering::SeqCst);
+}
+
+#[const_tweaker::tweak(on_change = "foreground_changed")]
+const FOREGROUND: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+};
+
+#[test]
+fn custom_type() {
+    assert_eq!(
+        BACKGROUND.get(),
+        Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0
+        }
+    );
+    assert_eq!(
+        const_tweaker::get_all()["BACKGROUND"],
+        json!({ "type": "Color", "value": [0.0, 0.0, 0.0] })
+    );
+
+    let mut snapshot = const_tweaker::take_snapshot();
+    snapshot.values.insert(
+        "BACKGROUND".to_string(),
+        json!({ "type": "Color", "value": [1.0, 0.5, 0.0] }),
+    );
+    const_tweaker::restore_snapshot(&snapshot);
+    assert_eq!(
+        BACKGROUND.get(),
+        Color {
+            r: 1.0,
+            g: 0.5,
+            b: 0.0
+        }
+    );
+
+    // Invalid values are not converted
+    snapshot.values.insert(
+        "BACKGROUND".to_string(),
+        json!({ "type": "Color", "value": [1.0] }),
+    );
+    const_tweaker::restore_snapshot(&snapshot);
+    assert_eq!(BACKGROUND.get().g, 0.5);
+
+    assert!(const_tweaker::reset_value("BACKGROUND"));
+    assert_eq!(BACKGROUND.get().r, 0.0);
+}
+
+#[test]
+fn custom_type_on_change() {
+    assert_eq!(FOREGROUND.get().r, 1.0);
+
+    let mut snapshot = const_tweaker::Snapshot::default();
+    snapshot.values.insert(
+        "FOREGROUND".to_string(),
+        json!({ "type": "Color", "value": [0.0, 1.0, 0.0] }),
+    );
+    const_tweaker::restore_snapshot(&snapshot);
+    assert!(CHANGED.load(Ordering::SeqCst));
+    assert_eq!(FOREGROUND.get().r, 0.0);
+}
+
+#[const_tweaker::tweak]
+const SPAWN_POINT: (f64, f64) = (1.0, 2.0);
+
+#[const_tweaker::tweak]
+const CAMERA_POSITION: (f64, f64, f64) = (0.0, 10.0, -5.0);
+
+#[test]
+fn tuples() {
+    assert_eq!(SPAWN_POINT.get(), (1.0, 2.0));
+    assert_eq!(CAMERA_POSITION.get(), (0.0, 10.0, -5.0));
+
+    let mut snapshot = const_tweaker::Snapshot::default();
+    snapshot.values.insert(
+        "SPAWN_POINT".to_string(),
+        json!({ "type": "(f64, f64)", "value": { "x": 3.0, "y": 4.0 } }),
+    );
+    snapshot.values.insert(
+        "CAMERA_POSITION".to_string(),
+        json!({ "type": "(f64, f64, f64)", "value": { "x": 1.0, "y": 2.0, "z": 3.0 } }),
+    );
+    const_tweaker::restore_snapshot(&snapshot);
+    assert_eq!(SPAWN_POINT.get(), (3.0, 4.0));
+    assert_eq!(CAMERA_POSITION.get(), (1.0, 2.0, 3.0));
+
+    let html = <(f64, f64)>::render_html("SPAWN_POINT", &(3.0, 4.0));
+    assert!(html.contains(">SPAWN_POINT::x<"));
+    assert!(html.contains(">SPAWN_POINT::y<"));
+}
+
+#[const_tweaker::tweak]
+const CLEAR_COLOR: [f32; 4] = [0.1, 0.2, 0.3, 1.0];
+
+#[test]
+fn color() {
+    assert_eq!(CLEAR_COLOR.get(), [0.1, 0.2, 0.3, 1.0]);
+
+    let html = <[f32; 4]>::render_html("CLEAR_COLOR", &[1.0, 0.0, 0.5, 1.0]);
+    assert!(html.contains(r##"value="#ff0080""##));
+
+    // Channels must be between 0 & 1
+    assert!(<[f32; 4]>::from_json(json!([2.0, 0.0, 0.0, 1.0])).is_err());
+    assert!(<[f32; 4]>::from_json(json!([1.0, 0.0, 0.0])).is_err());
+}
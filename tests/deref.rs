@@ -0,0 +1,32 @@
+//! The references handed out by `Deref` should stay valid when the value is changed.
+//!
+//! This doesn't start the server so it can also be run with `cargo miri test --test deref`.
+
+#[const_tweaker::tweak]
+const DEREF_VALUE: f64 = 0.5;
+
+#[const_tweaker::tweak]
+const DEREF_FLAG: bool = false;
+
+#[test]
+fn reference_outlives_change() {
+    let before: &f64 = &DEREF_VALUE;
+    assert_eq!(*before, 0.5);
+
+    assert!(const_tweaker::set_f64("DEREF_VALUE", 0.25));
+    assert_eq!(*DEREF_VALUE, 0.25);
+    // The old reference still points to the old value
+    assert_eq!(*before, 0.5);
+}
+
+#[test]
+fn reference_is_reused() {
+    assert!(!*DEREF_FLAG);
+
+    // Dereferencing an unchanged value doesn't leak a new value every time
+    assert!(std::ptr::eq(DEREF_FLAG.get(), DEREF_FLAG.get()));
+
+    assert!(const_tweaker::set_bool("DEREF_FLAG", true));
+    let after: &bool = &DEREF_FLAG;
+    assert!(*after);
+}
@@ -0,0 +1,104 @@
+use const_tweaker::Tweakable;
+use serde_json::json;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+#[derive(Debug, Clone, PartialEq, Tweakable)]
+struct Vec2 {
+    x: f64,
+    y: f64,
+}
+
+#[const_tweaker::tweak]
+const OFFSET: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+#[derive(Debug, Clone, PartialEq, Tweakable)]
+struct Settings {
+    name: String,
+    enabled: bool,
+    seed: i64,
+}
+
+#[test]
+fn derive_json() {
+    let settings = Settings {
+        name: "player".to_string(),
+        enabled: true,
+        seed: i64::MIN,
+    };
+    let json = settings.to_json();
+    assert_eq!(
+        json,
+        json!({ "name": "player", "enabled": true, "seed": "-9223372036854775808" })
+    );
+    assert_eq!(Settings::from_json(json).unwrap(), settings);
+    assert_eq!(Settings::type_name(), "Settings");
+
+    assert!(Settings::from_json(json!({ "name": "player" })).is_err());
+}
+
+#[test]
+fn derive_widgets() {
+    assert_eq!(OFFSET.get(), Vec2 { x: 0.0, y: 0.0 });
+
+    let tweaker = const_tweaker::run_on_random_port().expect("Could not run server");
+    let mut stream = TcpStream::connect(tweaker.addr()).expect("Could not connect to server");
+    write!(stream, "GET / HTTP/1.0\r\nHost: 127.0.0.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    assert!(response.contains(r#"id="OFFSET::x""#));
+    assert!(response.contains(r#"id="OFFSET::y""#));
+    assert!(response.contains(">OFFSET::x<"));
+    assert!(response.contains(">OFFSET::y<"));
+
+    // Select a variant of an enum by it's index
+    assert_eq!(QUALITY.get(), Quality::Medium);
+    let body = r#"{ "key": "QUALITY", "value": 2 }"#;
+    let mut stream = TcpStream::connect(tweaker.addr()).expect("Could not connect to server");
+    write!(
+        stream,
+        "POST /set/enum HTTP/1.0\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.0 200") || response.starts_with("HTTP/1.1 200"));
+    assert_eq!(QUALITY.get(), Quality::High);
+
+    tweaker.shutdown().unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Tweakable)]
+enum Quality {
+    Low,
+    Medium,
+    High,
+}
+
+#[const_tweaker::tweak]
+const QUALITY: Quality = Quality::Medium;
+
+#[test]
+fn derive_enum() {
+    assert_eq!(Quality::High.to_json(), json!(2));
+    assert_eq!(Quality::from_json(json!(0)).unwrap(), Quality::Low);
+    assert!(Quality::from_json(json!(3)).is_err());
+
+    let html = Quality::render_html("QUALITY", &Quality::Medium);
+    assert!(html.contains("<select"));
+    assert!(html.contains(r#"<option value="1" selected>Medium</option>"#));
+}
+
+#[const_tweaker::monitor]
+const POSITION: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+#[test]
+fn derive_set() {
+    assert!(POSITION.set(Vec2 { x: 1.0, y: -1.0 }));
+    assert_eq!(POSITION.get(), Vec2 { x: 1.0, y: -1.0 });
+}
@@ -0,0 +1,42 @@
+use std::env;
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const GRAVITY: f64 = 9.8;
+
+#[const_tweaker::tweak]
+const SEED: i64 = 0;
+
+#[const_tweaker::tweak]
+const DEBUG_MODE: bool = false;
+
+#[test]
+fn env_overrides() {
+    // Register the values
+    assert_eq!(*GRAVITY, 9.8);
+    assert_eq!(*SEED, 0);
+    assert!(!*DEBUG_MODE);
+
+    env::set_var("CONST_TWEAKER_GRAVITY", "19.6");
+    env::set_var("CONST_TWEAKER_SEED", "-9223372036854775808");
+    env::set_var("CONST_TWEAKER_DEBUG_MODE", "true");
+    env::set_var("CONST_TWEAKER_UNKNOWN_VALUE", "1");
+    assert_eq!(const_tweaker::apply_env_overrides().unwrap(), 3);
+    assert_eq!(*GRAVITY, 19.6);
+    assert_eq!(*SEED, i64::MIN);
+    assert!(*DEBUG_MODE);
+
+    // Nothing is changed when one of the values is invalid
+    env::set_var("CONST_TWEAKER_GRAVITY", "1.0");
+    env::set_var("CONST_TWEAKER_DEBUG_MODE", "yes");
+    assert!(matches!(
+        const_tweaker::apply_env_overrides(),
+        Err(const_tweaker::TweakerError::InvalidValue { .. })
+    ));
+    assert_eq!(*GRAVITY, 19.6);
+
+    // Out of bounds
+    env::set_var("CONST_TWEAKER_GRAVITY", "30.0");
+    env::set_var("CONST_TWEAKER_DEBUG_MODE", "false");
+    assert!(const_tweaker::apply_env_overrides().is_err());
+    assert_eq!(*GRAVITY, 19.6);
+}
@@ -0,0 +1,50 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const GRAVITY: f64 = 9.8;
+
+#[const_tweaker::tweak]
+const ENABLED: bool = true;
+
+/// Request the metrics and return the body.
+fn metrics(port: u16) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    write!(
+        stream,
+        "GET /metrics HTTP/1.0\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.contains("text/plain; version=0.0.4"));
+
+    response
+        .split("\r\n\r\n")
+        .nth(1)
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[test]
+fn values_and_changes() {
+    assert_eq!(*GRAVITY, 9.8);
+    assert!(*ENABLED);
+    let tweaker = const_tweaker::run_on_random_port().expect("Could not run server");
+
+    let body = metrics(tweaker.port());
+    assert!(body.contains("# TYPE const_tweaker_f64_value gauge\n"));
+    assert!(body.contains("const_tweaker_f64_value{key=\"GRAVITY\"} 9.8\n"));
+    assert!(body.contains("const_tweaker_bool_value{key=\"ENABLED\"} 1\n"));
+    assert!(body.contains("# TYPE const_tweaker_change_total counter\n"));
+
+    assert!(const_tweaker::set_bool("ENABLED", false));
+    let body = metrics(tweaker.port());
+    assert!(body.contains("const_tweaker_bool_value{key=\"ENABLED\"} 0\n"));
+    assert!(body.contains("const_tweaker_change_total 1\n"));
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
@@ -0,0 +1,209 @@
+use std::{env, fs, path::PathBuf, process, thread, time::Duration};
+
+#[const_tweaker::tweak]
+const SAVED_F64: f64 = 0.5;
+
+#[const_tweaker::tweak]
+const SAVED_BOOL: bool = false;
+
+/// A path in the temporary directory that's unique for this process & test.
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("const-tweaker-{}-{}.json", process::id(), name))
+}
+
+#[test]
+fn round_trip() {
+    assert_eq!(*SAVED_F64, 0.5);
+    assert!(!*SAVED_BOOL);
+
+    let path = temp_path("round-trip");
+    const_tweaker::save_to_file(&path).unwrap();
+
+    assert!(const_tweaker::set_f64("SAVED_F64", 0.25));
+    assert!(const_tweaker::set_bool("SAVED_BOOL", true));
+
+    const_tweaker::load_from_file(&path).unwrap();
+    assert_eq!(*SAVED_F64, 0.5);
+    assert!(!*SAVED_BOOL);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[const_tweaker::tweak]
+const LOADED_U8: u8 = 0;
+
+#[test]
+fn unknown_and_invalid_values() {
+    assert_eq!(*LOADED_U8, 0);
+
+    // Unknown values are ignored
+    let path = temp_path("unknown");
+    fs::write(
+        &path,
+        r#"{
+            "LOADED_U8": { "type": "u8", "value": 3 },
+            "UNKNOWN_VALUE": { "type": "f64", "value": 1.0 }
+        }"#,
+    )
+    .unwrap();
+    const_tweaker::load_from_file(&path).unwrap();
+    assert_eq!(*LOADED_U8, 3);
+
+    // Nothing is loaded when a value has the wrong type
+    fs::write(
+        &path,
+        r#"{ "LOADED_U8": { "type": "bool", "value": true } }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        const_tweaker::load_from_file(&path),
+        Err(const_tweaker::TweakerError::InvalidValue { .. })
+    ));
+    assert_eq!(*LOADED_U8, 3);
+
+    fs::write(&path, "not json").unwrap();
+    assert!(matches!(
+        const_tweaker::load_from_file(&path),
+        Err(const_tweaker::TweakerError::InvalidFile(_))
+    ));
+
+    fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        const_tweaker::load_from_file(&path),
+        Err(const_tweaker::TweakerError::Io(_))
+    ));
+}
+
+#[const_tweaker::tweak]
+const WATCHED_I32: i32 = 0;
+
+#[test]
+fn watch_file() {
+    assert_eq!(*WATCHED_I32, 0);
+
+    let path = temp_path("watch");
+    fs::write(&path, "{}").unwrap();
+    let watcher = const_tweaker::watch_file(&path).unwrap();
+
+    fs::write(&path, r#"{ "WATCHED_I32": { "type": "i32", "value": 7 } }"#).unwrap();
+    (0..100)
+        .find(|_| {
+            thread::sleep(Duration::from_millis(50));
+            *WATCHED_I32 == 7
+        })
+        .expect("The file wasn't reloaded");
+
+    // Changes are ignored after the handle is dropped
+    drop(watcher);
+    fs::write(&path, r#"{ "WATCHED_I32": { "type": "i32", "value": 8 } }"#).unwrap();
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(*WATCHED_I32, 7);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[const_tweaker::tweak]
+const SERIALIZED_I32: i32 = 1;
+
+#[test]
+fn json_string() {
+    assert_eq!(*SERIALIZED_I32, 1);
+
+    const_tweaker::from_json_str(r#"{ "SERIALIZED_I32": { "type": "i32", "value": 2 } }"#).unwrap();
+    assert_eq!(*SERIALIZED_I32, 2);
+
+    assert!(matches!(
+        const_tweaker::from_json_str("not json"),
+        Err(const_tweaker::TweakerError::InvalidJson(_))
+    ));
+    assert!(matches!(
+        const_tweaker::from_json_str(r#"{ "SERIALIZED_I32": { "type": "i32", "value": "a" } }"#),
+        Err(const_tweaker::TweakerError::InvalidValue { .. })
+    ));
+    assert_eq!(*SERIALIZED_I32, 2);
+}
+
+/// Checked for being on the default.
+#[const_tweaker::tweak]
+const CSV_STRING: String = "a";
+
+#[const_tweaker::tweak]
+const CSV_U8: u8 = 1;
+
+#[test]
+fn csv() {
+    assert_eq!(CSV_STRING.get(), "a");
+    assert_eq!(*CSV_U8, 1);
+
+    let csv = const_tweaker::export_csv();
+    assert!(csv.starts_with("key,type,default,current,min,max,description,locked\n"));
+    assert!(csv.contains("\nCSV_STRING,string,a,a,,,Checked for being on the default.,false\n"));
+    assert!(csv.contains("\nCSV_U8,u8,1,1,0,255,,false\n"));
+
+    // Fields with separators & quotes are quoted
+    let csv = "key,current\nCSV_STRING,\"b, \"\"c\"\"\"\nCSV_U8,2\nUNKNOWN_VALUE,1";
+    assert_eq!(const_tweaker::import_csv(csv).unwrap(), 2);
+    assert_eq!(CSV_STRING.get(), "b, \"c\"");
+    assert_eq!(*CSV_U8, 2);
+    assert!(const_tweaker::export_csv().contains("\nCSV_STRING,string,a,\"b, \"\"c\"\"\",,,"));
+
+    // Nothing is set when one of the values is invalid
+    let csv = "key,current\nCSV_U8,3\nCSV_STRING,\"unterminated";
+    assert!(matches!(
+        const_tweaker::import_csv(csv),
+        Err(const_tweaker::TweakerError::InvalidCsv(_))
+    ));
+    let csv = "key,current\nCSV_U8,3\nCSV_STRING,d\nCSV_U8,256";
+    assert!(matches!(
+        const_tweaker::import_csv(csv),
+        Err(const_tweaker::TweakerError::InvalidValue { .. })
+    ));
+    assert_eq!(*CSV_U8, 2);
+    assert!(const_tweaker::import_csv("key\nCSV_U8").is_err());
+}
+
+#[const_tweaker::tweak]
+const RECORDED_I32: i32 = 0;
+
+#[test]
+fn record_and_replay() {
+    assert_eq!(*RECORDED_I32, 0);
+
+    let path = temp_path("record");
+    let recorder = const_tweaker::record_changes_to_file(&path).unwrap();
+    const_tweaker::from_json_str(r#"{ "RECORDED_I32": { "type": "i32", "value": 1 } }"#).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    const_tweaker::from_json_str(r#"{ "RECORDED_I32": { "type": "i32", "value": 2 } }"#).unwrap();
+    recorder.stop();
+
+    // Changes after stopping aren't recorded
+    const_tweaker::from_json_str(r#"{ "RECORDED_I32": { "type": "i32", "value": 3 } }"#).unwrap();
+
+    // Other tests change values at the same time, so only the changes of this value are kept
+    let lines: Vec<String> = fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .filter(|line| line.contains("RECORDED_I32"))
+        .map(String::from)
+        .collect();
+    let values: Vec<serde_json::Value> = lines
+        .iter()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["value"].clone())
+        .collect();
+    assert_eq!(values, vec![1, 2]);
+    fs::write(&path, lines.join("\n")).unwrap();
+
+    let start = std::time::Instant::now();
+    const_tweaker::replay_from_file(&path, 2.0).unwrap();
+    assert_eq!(*RECORDED_I32, 2);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    for speed in &[0.0, -1.0, f64::NAN, f64::INFINITY] {
+        match const_tweaker::replay_from_file(&path, *speed) {
+            Err(const_tweaker::TweakerError::InvalidSpeed(_)) => (),
+            result => panic!("Expected an invalid speed error, got {:?}", result),
+        }
+    }
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,54 @@
+use const_tweaker::TweakerConfig;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+};
+
+/// Send a POST request without a body and return the HTTP status code.
+fn post(port: u16, path: &str) -> u16 {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        path
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response[9..12].parse().expect("Invalid status code")
+}
+
+#[const_tweaker::tweak]
+const LOCKED_VALUE: u16 = 1;
+
+#[const_tweaker::tweak]
+const UNLOCKED_VALUE: u16 = 1;
+
+// Resetting all values would interfere with other tests, so this is the only test in the file
+#[test]
+fn reset_locked() {
+    let config = TweakerConfig::builder().port(0).build();
+    let tweaker = const_tweaker::run_with_config(config).expect("Could not run server");
+
+    assert_eq!(*LOCKED_VALUE, 1);
+    assert_eq!(*UNLOCKED_VALUE, 1);
+    assert!(LOCKED_VALUE.set(2));
+    assert!(UNLOCKED_VALUE.set(2));
+    assert!(const_tweaker::lock_value("LOCKED_VALUE"));
+
+    assert_eq!(post(tweaker.port(), "/api/reset/LOCKED_VALUE"), 423);
+    assert_eq!(*LOCKED_VALUE, 2);
+
+    // Resetting all values skips the locked ones
+    assert_eq!(post(tweaker.port(), "/api/reset"), 200);
+    assert_eq!(*LOCKED_VALUE, 2);
+    assert_eq!(*UNLOCKED_VALUE, 1);
+
+    // The program can still reset it
+    assert!(const_tweaker::reset_value("LOCKED_VALUE"));
+    assert_eq!(*LOCKED_VALUE, 1);
+
+    tweaker.shutdown().expect("Could not shutdown server");
+}
@@ -0,0 +1,72 @@
+use const_tweaker::TweakerConfig;
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+/// Only a single server can run at the same time, so the tests can't run in parallel.
+static SERVER: Mutex<()> = Mutex::new(());
+
+/// Request the web page and return the HTTP status code.
+fn get_page(port: u16) -> u16 {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Could not connect to server");
+    write!(
+        stream,
+        "GET / HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response[9..12].parse().unwrap()
+}
+
+#[test]
+fn restart_on_same_port() {
+    let _server = SERVER.lock().unwrap();
+    let config = TweakerConfig::builder().port(9939).build();
+
+    for _ in 0..3 {
+        let tweaker = const_tweaker::run_with_config(config.clone()).expect("Could not run server");
+        assert_eq!(get_page(tweaker.port()), 200);
+
+        tweaker.shutdown().expect("Could not shutdown server");
+        assert!(TcpStream::connect(("127.0.0.1", 9939)).is_err());
+    }
+}
+
+#[test]
+fn port_fallback() {
+    let _server = SERVER.lock().unwrap();
+    // Occupy the first port of the range
+    let _listener = TcpListener::bind("127.0.0.1:9940").unwrap();
+
+    let tweaker = const_tweaker::run_with_port_fallback(9940, 9941).expect("Could not run server");
+    assert_eq!(tweaker.port(), 9941);
+    tweaker.shutdown().expect("Could not shutdown server");
+}
+
+#[test]
+fn run_async() {
+    let _server = SERVER.lock().unwrap();
+
+    async_std::task::block_on(async {
+        let config = TweakerConfig::builder().port(0).build();
+        let tweaker = const_tweaker::run_async_with_config(config)
+            .await
+            .expect("Could not run server");
+        let port = tweaker.port();
+
+        // Request the page from another thread, the executor has to keep serving meanwhile
+        let status = async_std::task::spawn_blocking(move || get_page(port)).await;
+        assert_eq!(status, 200);
+
+        tweaker
+            .shutdown_async()
+            .await
+            .expect("Could not shutdown server");
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_err());
+    });
+}
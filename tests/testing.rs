@@ -0,0 +1,112 @@
+use const_tweaker::testing;
+use serde_json::json;
+use std::panic;
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const GRAVITY: f64 = 9.8;
+
+#[const_tweaker::tweak]
+const DEBUG_DRAW: bool = false;
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const CLAMPED_GRAVITY: f64 = 50.0;
+
+#[test]
+fn with_values_clamped() {
+    assert_eq!(*CLAMPED_GRAVITY, 20.0);
+
+    testing::with_values(&[("CLAMPED_GRAVITY", json!(10.0))], || {
+        assert_eq!(*CLAMPED_GRAVITY, 10.0);
+    });
+
+    // The default outside of the bounds is restored as it was, so it's clamped when it's read
+    assert!(matches!(
+        const_tweaker::DATA.get("CLAMPED_GRAVITY").unwrap().value,
+        const_tweaker::Field::F64 { value, .. } if value == 50.0
+    ));
+    assert_eq!(*CLAMPED_GRAVITY.get(), 20.0);
+}
+
+#[test]
+fn with_values() {
+    assert_eq!(*GRAVITY, 9.8);
+    assert!(!*DEBUG_DRAW);
+
+    testing::with_values(
+        &[("GRAVITY", json!(1.5)), ("DEBUG_DRAW", json!(true))],
+        || {
+            assert_eq!(*GRAVITY, 1.5);
+            assert!(*DEBUG_DRAW);
+        },
+    );
+    assert_eq!(*GRAVITY, 9.8);
+    assert!(!*DEBUG_DRAW);
+
+    // The values are also restored when the test fails
+    let result = panic::catch_unwind(|| {
+        testing::with_values(&[("GRAVITY", json!(2.5))], || {
+            assert_eq!(*GRAVITY, 2.5);
+            panic!("failing test");
+        })
+    });
+    assert!(result.is_err());
+    assert_eq!(*GRAVITY, 9.8);
+
+    // Invalid values are rejected before anything is changed
+    let result = panic::catch_unwind(|| {
+        testing::with_values(
+            &[("DEBUG_DRAW", json!(true)), ("GRAVITY", json!(30.0))],
+            || (),
+        )
+    });
+    assert!(result.is_err());
+    assert!(!*DEBUG_DRAW);
+}
+
+#[const_tweaker::tweak]
+const SPEED: f64 = 0.0;
+
+#[test]
+fn set_values() {
+    assert_eq!(*SPEED, 0.0);
+
+    testing::set_f64("SPEED", 0.5);
+    assert_eq!(*SPEED, 0.5);
+
+    assert!(panic::catch_unwind(|| testing::set_bool("SPEED", true)).is_err());
+}
+
+#[const_tweaker::tweak]
+const MOCKED_NAME: String = "player";
+
+#[derive(Debug, Clone, PartialEq, const_tweaker::Tweakable)]
+struct Vec2 {
+    x: f64,
+    y: f64,
+}
+
+#[const_tweaker::tweak]
+const MOCKED_OFFSET: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+#[test]
+fn mock_tweaks() {
+    {
+        const_tweaker::mock_tweaks! {
+            GRAVITY => 1.0,
+            MOCKED_NAME => "enemy".to_string(),
+            MOCKED_OFFSET => Vec2 { x: 1.0, y: 2.0 },
+        }
+        assert_eq!(*GRAVITY, 1.0);
+        assert_eq!(MOCKED_NAME.get(), "enemy");
+        assert_eq!(MOCKED_OFFSET.get(), Vec2 { x: 1.0, y: 2.0 });
+
+        // Other threads aren't affected
+        std::thread::spawn(|| assert_eq!(*GRAVITY, 9.8))
+            .join()
+            .unwrap();
+    }
+
+    assert_eq!(*GRAVITY, 9.8);
+    assert_eq!(MOCKED_NAME.get(), "player");
+    assert_eq!(MOCKED_OFFSET.get(), Vec2 { x: 0.0, y: 0.0 });
+}
@@ -0,0 +1,609 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    },
+    thread,
+    time::Duration,
+};
+
+#[const_tweaker::tweak]
+const I64_VALUE: i64 = 0;
+
+static START: Once = Once::new();
+
+/// Start the tweaker server and connect to it.
+fn connect() -> TcpStream {
+    // The server is shared by all tests and keeps running when the handle is dropped
+    START.call_once(|| {
+        let _ = const_tweaker::run().expect("Could not run server");
+    });
+
+    // Wait for the server thread to start listening
+    (0..50)
+        .find_map(|_| {
+            TcpStream::connect("127.0.0.1:9938")
+                .map_err(|_| thread::sleep(Duration::from_millis(100)))
+                .ok()
+        })
+        .expect("Could not connect to server")
+}
+
+/// Send a GET request to the running tweaker server and return the body.
+fn get(path: &str) -> String {
+    let mut stream = connect();
+
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n",
+        path
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response
+}
+
+/// Send a JSON POST request to the running tweaker server and return the HTTP status code.
+fn post(path: &str, body: &str) -> u16 {
+    let mut stream = connect();
+
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    // Parse the code from the "HTTP/1.1 200 OK" status line
+    response[9..12].parse().unwrap()
+}
+
+#[test]
+fn i64_round_trip() {
+    // Register the value
+    assert_eq!(*I64_VALUE, 0);
+
+    for value in &[i64::MAX, i64::MIN] {
+        let body = format!(r#"{{"key":"I64_VALUE","value":"{}"}}"#, value);
+        assert_eq!(post("/set/i64", &body), 200);
+        assert_eq!(*I64_VALUE, *value);
+    }
+
+    let body = r#"{"key":"I64_VALUE","value":"9223372036854775808"}"#;
+    assert_eq!(post("/set/i64", body), 400);
+    assert_eq!(*I64_VALUE, i64::MIN);
+}
+
+#[const_tweaker::tweak]
+const I8_VALUE: i8 = 0;
+
+#[const_tweaker::tweak]
+const I16_VALUE: i16 = 0;
+
+#[test]
+fn i8_boundaries() {
+    assert_eq!(*I8_VALUE, 0);
+
+    for value in &[127, -127, -128] {
+        let body = format!(r#"{{"key":"I8_VALUE","value":{}}}"#, value);
+        assert_eq!(post("/set/i8", &body), 200);
+        assert_eq!(*I8_VALUE, *value);
+    }
+
+    assert_eq!(post("/set/i8", r#"{"key":"I8_VALUE","value":128}"#), 422);
+    assert_eq!(post("/set/i8", r#"{"key":"I8_VALUE","value":-129}"#), 422);
+    assert_eq!(post("/set/i8", r#"{"key":"I8_VALUE","value":1e20}"#), 422);
+
+    // Only numbers outside of the range are unprocessable, other invalid bodies are bad requests
+    assert_eq!(post("/set/i8", r#"{"key":"I8_VALUE","#), 400);
+    assert_eq!(post("/set/i8", r#"{"value":1}"#), 400);
+    assert_eq!(post("/set/i8", r#"{"key":"I8_VALUE","value":1.5}"#), 400);
+    assert_eq!(post("/set/i8", r#"{"key":"I8_VALUE","value":"1"}"#), 400);
+    assert_eq!(*I8_VALUE, -128);
+}
+
+#[test]
+fn i16_boundaries() {
+    assert_eq!(*I16_VALUE, 0);
+
+    for value in &[32767, -32767, -32768] {
+        let body = format!(r#"{{"key":"I16_VALUE","value":{}}}"#, value);
+        assert_eq!(post("/set/i16", &body), 200);
+        assert_eq!(*I16_VALUE, *value);
+    }
+
+    assert_eq!(
+        post("/set/i16", r#"{"key":"I16_VALUE","value":32768}"#),
+        422
+    );
+    assert_eq!(
+        post("/set/i16", r#"{"key":"I16_VALUE","value":-32769}"#),
+        422
+    );
+    assert_eq!(post("/set/i16", r#"{"value":1}"#), 400);
+    assert_eq!(*I16_VALUE, -32768);
+}
+
+#[const_tweaker::tweak]
+const U8_VALUE: u8 = 0;
+
+#[test]
+fn u8_range() {
+    assert_eq!(*U8_VALUE, 0);
+
+    assert_eq!(post("/set/u8", r#"{"key":"U8_VALUE","value":255}"#), 200);
+    assert_eq!(post("/set/u8", r#"{"key":"U8_VALUE","value":256}"#), 400);
+    assert_eq!(post("/set/u8", r#"{"key":"U8_VALUE","value":-1}"#), 400);
+    assert_eq!(post("/set/u8", r#"{"value":1}"#), 400);
+    assert_eq!(*U8_VALUE, 255);
+}
+
+#[const_tweaker::tweak]
+const U128_VALUE: u128 = 0;
+
+#[const_tweaker::tweak]
+const I128_VALUE: i128 = 0;
+
+#[test]
+fn u128_round_trip() {
+    assert_eq!(*U128_VALUE, 0);
+
+    for value in &[u128::MAX, u128::MIN] {
+        let body = format!(r#"{{"key":"U128_VALUE","value":"{}"}}"#, value);
+        assert_eq!(post("/set/u128", &body), 200);
+        assert_eq!(*U128_VALUE, *value);
+    }
+
+    assert_eq!(
+        post("/set/u128", r#"{"key":"U128_VALUE","value":"-1"}"#),
+        400
+    );
+}
+
+#[test]
+fn i128_round_trip() {
+    assert_eq!(*I128_VALUE, 0);
+
+    for value in &[i128::MAX, i128::MIN] {
+        let body = format!(r#"{{"key":"I128_VALUE","value":"{}"}}"#, value);
+        assert_eq!(post("/set/i128", &body), 200);
+        assert_eq!(*I128_VALUE, *value);
+    }
+
+    let body = format!(r#"{{"key":"I128_VALUE","value":"{}0"}}"#, i128::MAX);
+    assert_eq!(post("/set/i128", &body), 400);
+}
+
+#[const_tweaker::tweak]
+const F64_UNBOUNDED_VALUE: f64 = 250.0;
+
+#[test]
+fn f64_default_bounds() {
+    assert_eq!(*F64_UNBOUNDED_VALUE, 250.0);
+
+    // The default bounds include the default value
+    assert!(const_tweaker::set_f64("F64_UNBOUNDED_VALUE", 9.9));
+    assert_eq!(
+        post(
+            "/set/f64",
+            r#"{"key":"F64_UNBOUNDED_VALUE","value":-100.0}"#
+        ),
+        200
+    );
+    assert_eq!(
+        post("/set/f64", r#"{"key":"F64_UNBOUNDED_VALUE","value":250.0}"#),
+        200
+    );
+    assert_eq!(
+        post("/set/f64", r#"{"key":"F64_UNBOUNDED_VALUE","value":250.1}"#),
+        400
+    );
+    assert_eq!(*F64_UNBOUNDED_VALUE, 250.0);
+}
+
+#[const_tweaker::tweak(min = 0.0, max = 1.0)]
+const F64_BOUNDED_VALUE: f64 = 0.5;
+
+#[test]
+fn f64_bounds() {
+    assert_eq!(*F64_BOUNDED_VALUE, 0.5);
+
+    for value in &[0.0, 1.0] {
+        let body = format!(r#"{{"key":"F64_BOUNDED_VALUE","value":{:?}}}"#, value);
+        assert_eq!(post("/set/f64", &body), 200);
+        assert_eq!(*F64_BOUNDED_VALUE, *value);
+    }
+
+    assert_eq!(
+        post("/set/f64", r#"{"key":"F64_BOUNDED_VALUE","value":1.5}"#),
+        400
+    );
+    assert_eq!(
+        post("/set/f64", r#"{"key":"F64_BOUNDED_VALUE","value":-0.1}"#),
+        400
+    );
+    assert_eq!(*F64_BOUNDED_VALUE, 1.0);
+}
+
+#[const_tweaker::tweak]
+const LOCKED_VALUE: bool = false;
+
+#[test]
+fn locked_value() {
+    assert!(!*LOCKED_VALUE);
+    assert_eq!(post("/api/lock/LOCKED_VALUE", ""), 200);
+
+    assert_eq!(
+        post("/set/bool", r#"{"key":"LOCKED_VALUE","value":true}"#),
+        423
+    );
+    assert!(!*LOCKED_VALUE);
+
+    assert!(const_tweaker::unlock_value("LOCKED_VALUE"));
+    assert_eq!(
+        post("/set/bool", r#"{"key":"LOCKED_VALUE","value":true}"#),
+        200
+    );
+    assert!(*LOCKED_VALUE);
+
+    assert_eq!(post("/api/lock/MISSING_VALUE", ""), 404);
+}
+
+#[const_tweaker::tweak]
+const RESET_VALUE: u16 = 10;
+
+#[test]
+fn reset_value() {
+    assert_eq!(*RESET_VALUE, 10);
+
+    assert_eq!(post("/set/u16", r#"{"key":"RESET_VALUE","value":20}"#), 200);
+    assert_eq!(*RESET_VALUE, 20);
+
+    assert_eq!(post("/api/reset/RESET_VALUE", ""), 200);
+    assert_eq!(*RESET_VALUE, 10);
+
+    assert_eq!(post("/api/reset/UNKNOWN_VALUE", ""), 404);
+}
+
+static CALLBACK_VALUE: AtomicBool = AtomicBool::new(false);
+
+fn store_callback_value(value: bool) {
+    CALLBACK_VALUE.store(value, Ordering::SeqCst);
+}
+
+#[const_tweaker::tweak(on_change = "store_callback_value")]
+const CALLBACK_TWEAK: bool = false;
+
+#[test]
+fn on_change_callback() {
+    assert!(!*CALLBACK_TWEAK);
+
+    assert_eq!(
+        post("/set/bool", r#"{"key":"CALLBACK_TWEAK","value":true}"#),
+        200
+    );
+    assert!(CALLBACK_VALUE.load(Ordering::SeqCst));
+
+    assert!(const_tweaker::reset_value("CALLBACK_TWEAK"));
+    assert!(!CALLBACK_VALUE.load(Ordering::SeqCst));
+}
+
+static CHAINED_VALUE: AtomicBool = AtomicBool::new(false);
+
+fn read_chained_target(value: bool) {
+    // The first read registers the callback of the other value
+    CHAINED_VALUE.store(value && *CHAINED_TARGET, Ordering::SeqCst);
+}
+
+fn cap_chained_source(_: bool) {}
+
+#[const_tweaker::tweak(on_change = "read_chained_target")]
+const CHAINED_SOURCE: bool = false;
+
+#[const_tweaker::tweak(on_change = "cap_chained_source")]
+const CHAINED_TARGET: bool = true;
+
+fn cap_value(value: u16) {
+    if value > 10 {
+        CAPPED_VALUE.set(10);
+    }
+}
+
+#[const_tweaker::tweak(min = 0, max = 100, on_change = "cap_value")]
+const CAPPED_VALUE: u16 = 0;
+
+#[test]
+fn on_change_reentrant() {
+    assert!(!*CHAINED_SOURCE);
+    assert!(const_tweaker::set_bool("CHAINED_SOURCE", true));
+    assert!(CHAINED_VALUE.load(Ordering::SeqCst));
+
+    // The callback can change its own value
+    assert_eq!(*CAPPED_VALUE, 0);
+    assert_eq!(
+        post("/set/u16", r#"{"key":"CAPPED_VALUE","value":20}"#),
+        200
+    );
+    assert_eq!(*CAPPED_VALUE, 10);
+}
+
+#[const_tweaker::tweak]
+const WATCHED_VALUE: f64 = 0.0;
+
+#[test]
+fn watch_f64() {
+    assert_eq!(*WATCHED_VALUE, 0.0);
+    assert!(const_tweaker::watch_f64("UNKNOWN_VALUE").is_none());
+    assert!(const_tweaker::watch_bool("WATCHED_VALUE").is_none());
+
+    let receiver = const_tweaker::watch_f64("WATCHED_VALUE").unwrap();
+    assert_eq!(
+        post("/set/f64", r#"{"key":"WATCHED_VALUE","value":0.5}"#),
+        200
+    );
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(1)), Ok(0.5));
+}
+
+#[const_tweaker::tweak]
+const SET_BOOL_VALUE: bool = false;
+
+#[test]
+fn set_bool() {
+    assert!(!*SET_BOOL_VALUE);
+
+    assert!(const_tweaker::set_bool("SET_BOOL_VALUE", true));
+    assert!(*SET_BOOL_VALUE);
+
+    assert!(!const_tweaker::set_f64("SET_BOOL_VALUE", 1.0));
+    assert!(!const_tweaker::set_bool("UNKNOWN_VALUE", true));
+}
+
+#[const_tweaker::tweak]
+const EVENT_VALUE: u8 = 0;
+
+#[test]
+fn server_sent_events() {
+    assert_eq!(*EVENT_VALUE, 0);
+
+    let mut stream = connect();
+    write!(
+        stream,
+        "GET /events HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Wait for the response headers so the client is registered before the value changes
+    let mut response = String::new();
+    let mut buffer = [0; 1024];
+    while !response.contains("\r\n\r\n") {
+        let read = stream.read(&mut buffer).unwrap();
+        response.push_str(&String::from_utf8_lossy(&buffer[..read]));
+    }
+
+    assert_eq!(post("/set/u8", r#"{"key":"EVENT_VALUE","value":10}"#), 200);
+
+    let event = r#"data: {"changes":1,"key":"EVENT_VALUE","type":"u8","value":10}"#;
+    while !response.contains(event) {
+        let read = stream.read(&mut buffer).unwrap();
+        response.push_str(&String::from_utf8_lossy(&buffer[..read]));
+    }
+}
+
+#[test]
+fn already_running() {
+    drop(connect());
+
+    assert!(matches!(
+        const_tweaker::run(),
+        Err(const_tweaker::TweakerError::AlreadyRunning)
+    ));
+}
+
+#[const_tweaker::tweak]
+const LOG_VALUE: f64 = 0.0;
+
+#[test]
+fn log_fn() {
+    assert_eq!(*LOG_VALUE, 0.0);
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let sender = std::sync::Mutex::new(sender);
+    const_tweaker::set_log_fn(move |key, old_value, new_value| {
+        // Other tests change values at the same time
+        if key == "LOG_VALUE" {
+            let _ = sender.lock().unwrap().send((old_value, new_value));
+        }
+    });
+
+    assert!(const_tweaker::set_f64("LOG_VALUE", 0.5));
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+        (serde_json::json!(0.0), serde_json::json!(0.5))
+    );
+
+    // The function can replace itself
+    const_tweaker::set_log_fn(|key, _, _| {
+        if key == "LOG_VALUE" {
+            const_tweaker::set_log_fn(|_, _, _| {});
+        }
+    });
+    assert!(const_tweaker::set_f64("LOG_VALUE", 0.25));
+    assert!(const_tweaker::set_f64("LOG_VALUE", 0.0));
+}
+
+#[const_tweaker::tweak]
+const MODIFIED_VALUE: f64 = 0.5;
+
+#[test]
+fn modified_tweaks() {
+    assert_eq!(*MODIFIED_VALUE, 0.5);
+    let is_listed = || {
+        const_tweaker::modified_tweaks()
+            .iter()
+            .any(|info| info.key == "MODIFIED_VALUE")
+    };
+    assert!(!is_listed());
+
+    assert!(const_tweaker::set_f64("MODIFIED_VALUE", 0.25));
+    assert!(const_tweaker::is_modified("MODIFIED_VALUE"));
+    assert!(is_listed());
+
+    // Changing it back to the default isn't a modification
+    assert!(const_tweaker::set_f64("MODIFIED_VALUE", 0.5));
+    assert!(!const_tweaker::is_modified("MODIFIED_VALUE"));
+    assert!(!is_listed());
+}
+
+#[const_tweaker::tweak]
+const MALFORMED_VALUE: bool = false;
+
+#[test]
+fn malformed_json() {
+    assert!(!*MALFORMED_VALUE);
+
+    assert_eq!(post("/set/f64", r#"{"key":"#), 400);
+    assert_eq!(post("/set/bool", r#"{"value":true}"#), 400);
+    assert_eq!(
+        post("/set/bool", r#"{"key":"MISSING_VALUE","value":true}"#),
+        404
+    );
+
+    // The server keeps running
+    assert_eq!(
+        post("/set/bool", r#"{"key":"MALFORMED_VALUE","value":true}"#),
+        200
+    );
+    assert!(*MALFORMED_VALUE);
+}
+
+#[const_tweaker::monitor]
+const ENEMY_COUNT: u32 = 0;
+
+#[const_tweaker::tweak]
+const PLAYER_NAME: String = "player";
+
+#[test]
+fn monitor_set() {
+    assert!(ENEMY_COUNT.set(12));
+    assert_eq!(*ENEMY_COUNT, 12);
+    assert_eq!(post("/set/u32", r#"{"key":"ENEMY_COUNT","value":1}"#), 403);
+    assert_eq!(*ENEMY_COUNT, 12);
+
+    assert!(PLAYER_NAME.set("enemy".to_string()));
+    assert_eq!(PLAYER_NAME.get(), "enemy");
+}
+
+#[const_tweaker::tweak(label = "Gravity (m/s²)", group = "Physics")]
+static LABELED_GRAVITY: f64 = 9.8;
+
+#[const_tweaker::tweak(label = "Wind direction")]
+const LABELED_WIND: (f64, f64) = (1.0, 0.0);
+
+#[test]
+fn label() {
+    assert_eq!(*LABELED_GRAVITY, 9.8);
+    assert_eq!(LABELED_WIND.get(), (1.0, 0.0));
+
+    // The label is shown instead of the key, which is still used to change the value
+    let page = get("/");
+    assert!(page.contains(r#"<span class="tag">Gravity (m/s²)</span>"#));
+    assert!(page.contains(r#"<span class="tag">Wind direction</span>"#));
+    assert!(page.contains(r#"data-search="labeled_gravity gravity (m/s²)  physics""#));
+    assert!(!page.contains(r#"<span class="tag">LABELED_GRAVITY</span>"#));
+    assert!(const_tweaker::set_f64("LABELED_GRAVITY", 9.81));
+}
+
+mod physics {
+    #[const_tweaker::tweak]
+    const DUPLICATE_VALUE: f64 = 1.0;
+
+    pub fn read() -> f64 {
+        *DUPLICATE_VALUE
+    }
+}
+
+mod audio {
+    #[const_tweaker::tweak]
+    const DUPLICATE_VALUE: f64 = 0.5;
+
+    pub fn read() -> f64 {
+        *DUPLICATE_VALUE
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Duplicate tweak key: DUPLICATE_VALUE")]
+fn duplicate_key() {
+    assert_eq!(physics::read(), 1.0);
+    audio::read();
+}
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const ASSERTED_GRAVITY: f64 = 9.8;
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(
+    expected = "Tweak assertion failed for ASSERTED_GRAVITY = 0.0: Gravity must be positive"
+)]
+fn tweak_assert() {
+    const_tweaker::tweak_assert! { ASSERTED_GRAVITY > 0.0, "Gravity must be positive" }
+    const_tweaker::tweak_assert! { ASSERTED_GRAVITY < 100.0 }
+    assert_eq!(*ASSERTED_GRAVITY, 9.8);
+
+    // Values set from code aren't limited by the slider bounds
+    assert!(const_tweaker::set_f64("ASSERTED_GRAVITY", 0.0));
+    let _ = *ASSERTED_GRAVITY;
+}
+
+#[const_tweaker::tweak(min = 0.0, max = 20.0)]
+const CONSTRAINED_GRAVITY: f64 = 50.0;
+
+#[const_tweaker::tweak(min = 1, max = 8)]
+const CONSTRAINED_COUNT: u8 = 0;
+
+#[test]
+fn clamp_to_bounds() {
+    assert_eq!(*CONSTRAINED_GRAVITY, 20.0);
+    assert_eq!(*CONSTRAINED_COUNT, 1);
+
+    // Values outside of the bounds can't be set from code either
+    assert!(!const_tweaker::set_f64("CONSTRAINED_GRAVITY", 9999.0));
+    assert_eq!(*CONSTRAINED_GRAVITY, 20.0);
+
+    assert!(const_tweaker::set_f64("CONSTRAINED_GRAVITY", 5.0));
+    assert_eq!(*CONSTRAINED_GRAVITY, 5.0);
+
+    // Resetting stores the default outside of the bounds again, which is clamped when it's read
+    assert!(const_tweaker::reset_value("CONSTRAINED_GRAVITY"));
+    assert!(matches!(
+        const_tweaker::DATA.get("CONSTRAINED_GRAVITY").unwrap().value,
+        const_tweaker::Field::F64 { value, .. } if value == 50.0
+    ));
+    assert_eq!(*CONSTRAINED_GRAVITY, 20.0);
+    assert_eq!(*CONSTRAINED_GRAVITY.get(), 20.0);
+}
+
+#[const_tweaker::tweak(min = 0.0, max = 100.0, group = "Physics")]
+static STATIC_GRAVITY: f64 = 9.8;
+
+#[test]
+fn static_item() {
+    assert_eq!(*STATIC_GRAVITY, 9.8);
+    assert!(const_tweaker::set_f64("STATIC_GRAVITY", 19.6));
+    assert_eq!(*STATIC_GRAVITY, 19.6);
+}